@@ -0,0 +1,59 @@
+//! Throughput of the corpus indexing over the bundled `public_domain/` books.
+//!
+//! Run from the repository root with `cargo bench --bench corpus`.
+mod support;
+
+use gamo::apps::booktore;
+use std::time::{Duration, Instant};
+use support::Counter;
+
+#[global_allocator]
+static ALLOCATOR: Counter = Counter;
+
+const ROUNDS: u32 = 3;
+
+fn measure<T, F: Fn() -> T>(name: &str, bytes: usize, fnn: F) -> T {
+    let mut best = Duration::MAX;
+    let mut result = None;
+    for _ in 0..ROUNDS {
+        let start = Instant::now();
+        result = Some(fnn());
+        best = best.min(start.elapsed());
+    }
+    let megabytes = bytes as f64 / 1_000_000.0;
+    println!(
+        "{:<12} {:>8.1} ms {:>8.1} MB/s",
+        name,
+        best.as_secs_f64() * 1000.0,
+        megabytes / best.as_secs_f64()
+    );
+    result.unwrap()
+}
+
+fn main() {
+    let books = measure("read", 0, booktore::read_books);
+    let bytes = books.iter().map(|book| book.len()).sum();
+    println!(
+        "{} books, {:.1} MB, {} threads",
+        books.len(),
+        bytes as f64 / 1_000_000.0,
+        rayon::current_num_threads()
+    );
+
+    // First, so the memory of interning the corpus is counted.
+    let (corpus, usage) = support::measure(|| booktore::index(&books));
+    usage.print();
+    drop(corpus);
+
+    let sequential = measure("sequential", bytes, || booktore::index_sequential(&books));
+    let parallel = measure("parallel", bytes, || booktore::index(&books));
+    assert!(
+        sequential == parallel,
+        "parallel index differs from sequential"
+    );
+    println!(
+        "{} tokens, {} distinct words",
+        parallel.tokens(),
+        parallel.words()
+    );
+}
@@ -0,0 +1,64 @@
+//! Counting allocator shared by the benches.
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    sync::atomic::{AtomicUsize, Ordering::Relaxed},
+};
+
+pub struct Counter;
+
+static CURRENT: AtomicUsize = AtomicUsize::new(0);
+static PEAK: AtomicUsize = AtomicUsize::new(0);
+static TOTAL: AtomicUsize = AtomicUsize::new(0);
+static COUNT: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for Counter {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let current = CURRENT.fetch_add(layout.size(), Relaxed) + layout.size();
+        PEAK.fetch_max(current, Relaxed);
+        TOTAL.fetch_add(layout.size(), Relaxed);
+        COUNT.fetch_add(1, Relaxed);
+        System.alloc(layout)
+    }
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        CURRENT.fetch_sub(layout.size(), Relaxed);
+        System.dealloc(ptr, layout)
+    }
+}
+
+/// Bytes held, peak, allocated and number of allocations while `fnn` ran,
+/// the value it returns is still alive when `retained` is taken.
+#[derive(Debug)]
+pub struct Usage {
+    pub retained: usize,
+    pub peak: usize,
+    pub total: usize,
+    pub count: usize,
+}
+
+impl Usage {
+    pub fn print(&self) {
+        let mb = |n: usize| n as f64 / 1_000_000.0;
+        println!("retained   {:>8.1} MB", mb(self.retained));
+        println!("peak       {:>8.1} MB", mb(self.peak));
+        println!(
+            "allocated  {:>8.1} MB in {} allocations",
+            mb(self.total),
+            self.count
+        );
+    }
+}
+
+pub fn measure<T, F: FnOnce() -> T>(fnn: F) -> (T, Usage) {
+    let base = CURRENT.load(Relaxed);
+    PEAK.store(base, Relaxed);
+    TOTAL.store(0, Relaxed);
+    COUNT.store(0, Relaxed);
+    let value = fnn();
+    let usage = Usage {
+        retained: CURRENT.load(Relaxed).saturating_sub(base),
+        peak: PEAK.load(Relaxed) - base,
+        total: TOTAL.load(Relaxed),
+        count: COUNT.load(Relaxed),
+    };
+    (value, usage)
+}
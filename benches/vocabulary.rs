@@ -0,0 +1,87 @@
+//! Memory used to load a large synthetic vocabulary with `gamo::start`.
+//!
+//! Run from the repository root with `cargo bench --bench vocabulary`. The
+//! vocabulary is written to a temporary directory, which becomes the current
+//! directory while it is loaded.
+mod support;
+
+use std::{env, fs, time::Instant};
+use support::Counter;
+
+const WORDS: usize = 200_000;
+const TYPES: [&str; 3] = ["ANY", "esencial", "noEsencial"];
+
+#[global_allocator]
+static ALLOCATOR: Counter = Counter;
+
+/// Deterministic pseudo random words, spread over the length folders.
+fn words() -> Vec<String> {
+    let mut seed: u64 = 0x2545_f491_4f6c_dd1d;
+    (0..WORDS)
+        .map(|index| {
+            let len = 2 + index % 14;
+            (0..len)
+                .map(|_| {
+                    seed ^= seed << 13;
+                    seed ^= seed >> 7;
+                    seed ^= seed << 17;
+                    (b'a' + (seed % 26) as u8) as char
+                })
+                .collect()
+        })
+        .collect()
+}
+
+fn write_tree(root: &std::path::Path) -> usize {
+    let config = root.join("config");
+    fs::create_dir_all(&config).unwrap();
+    let folders: Vec<String> = (2..16).map(|n| n.to_string()).collect();
+    fs::write(config.join("folders.on"), folders.join("\n")).unwrap();
+    fs::write(config.join("types.on"), TYPES.join("\n")).unwrap();
+    fs::write(config.join("rename.on"), "").unwrap();
+
+    let mut bytes = 0;
+    let list = words();
+    for folder in &folders {
+        let dir = root.join("vocabulary").join(folder);
+        fs::create_dir_all(&dir).unwrap();
+        for (index, tipo) in TYPES.iter().enumerate() {
+            let content: String = list
+                .iter()
+                .filter(|word| &word.len().to_string() == folder)
+                .skip(index)
+                .step_by(TYPES.len())
+                .fold(String::new(), |mut acc, word| {
+                    acc.push_str(word);
+                    acc.push('\n');
+                    acc
+                });
+            bytes += content.len();
+            fs::write(dir.join(format!("{}.on", tipo)), content).unwrap();
+        }
+    }
+    bytes
+}
+
+fn main() {
+    let root = env::temp_dir().join(format!("gamo-bench-{}", std::process::id()));
+    let bytes = write_tree(&root);
+    let previous = env::current_dir().unwrap();
+    env::set_current_dir(&root).unwrap();
+
+    let start = Instant::now();
+    let (sphere, usage) = support::measure(|| gamo::start(""));
+    let elapsed = start.elapsed();
+
+    env::set_current_dir(previous).unwrap();
+    fs::remove_dir_all(&root).unwrap();
+
+    println!(
+        "{} words, {:.1} MB of word files",
+        WORDS,
+        bytes as f64 / 1_000_000.0
+    );
+    println!("load       {:>8.1} ms", elapsed.as_secs_f64() * 1000.0);
+    usage.print();
+    drop(sphere);
+}
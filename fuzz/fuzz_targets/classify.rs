@@ -0,0 +1,13 @@
+#![no_main]
+use gamo::apps::{intern::Word, mas, token::Str};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|input: (&str, &str, u8)| {
+    let (on, off, split) = input;
+    let vocabulary = Word::list(off.split_whitespace().take(3));
+    let prefix = on.get(..split as usize);
+    let set = mas::classify(on, off, &vocabulary, prefix, None);
+    for word in &set.list {
+        assert!(Str::valid_english(word));
+    }
+});
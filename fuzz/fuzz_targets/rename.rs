@@ -0,0 +1,11 @@
+#![no_main]
+use gamo::apps::{ReadParseFile, Rename};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|content: &str| {
+    let content = content.to_owned();
+    let rename = ReadParseFile { content }.split_rename(Rename::new());
+    for change in rename.changes {
+        assert!(!change.from.is_empty() && !change.to.is_empty());
+    }
+});
@@ -0,0 +1,22 @@
+#![no_main]
+use gamo::apps::{
+    normal,
+    token::{self, Str},
+};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|input: &str| {
+    let word = Str::rm_start_end(input);
+    assert_eq!(Str::rm_start_end(word), word);
+    let word = token::parse_word(input);
+    assert_eq!(token::parse_word(&word), word);
+    if let Some(word) = token::word(input) {
+        assert_eq!(token::word(&word), Some(word));
+    }
+    let folded = normal::fold(input);
+    assert_eq!(normal::fold(&folded), folded);
+    Str::is_ing(input);
+    Str::is_ed(input);
+    Str::is_plural(input);
+    Str::is_match(input);
+});
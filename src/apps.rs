@@ -1,12 +1,42 @@
+pub mod anki;
+pub mod api;
 pub mod booktore;
+pub mod build;
+pub mod case;
+pub mod checksum;
+pub mod cloze;
+pub mod deny;
+pub mod export;
+pub mod fixture;
+pub mod import;
+pub mod intern;
+pub mod journal;
+pub mod level;
 pub mod mas;
-
+pub mod normal;
+pub mod off;
+pub mod output;
+pub mod release;
+#[cfg(feature = "serve")]
+pub mod serve;
+pub mod store;
+pub mod study;
+pub mod token;
+pub mod triage;
+
+use build::BuildFormat;
+use intern::Word;
+use level::Level;
 use std::{
     collections::HashMap,
     env, fs,
     path::{Path, PathBuf},
-    usize,
 };
+use store::{DirStore, MemoryStore, VocabularyStore};
+
+/// Positions in `Vocabulary::data_all`, grouped by a key.
+type Index = HashMap<String, Vec<usize>>;
+type Layout = HashMap<String, Index>;
 
 #[derive(Debug, Clone)]
 pub struct Change {
@@ -21,21 +51,30 @@ pub struct Rename {
     pub file_path: PathBuf,
     pub comments: Vec<String>,
     pub changes: Vec<Change>,
+    /// Lines that are not a comment nor a `from: <tipo> to: <tipo>`.
+    pub malformed: Vec<String>,
 }
+impl Default for Rename {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl Rename {
-    fn new() -> Self {
+    pub fn new() -> Self {
         Self {
             name: String::new(),
             file_name: String::new(),
             comments: Vec::new(),
             changes: Vec::new(),
+            malformed: Vec::new(),
             file_path: PathBuf::new(),
         }
     }
-    fn setup(mut self, config: &Config, _sphere: &Sphere) -> Self {
+    fn setup(mut self, dir: &Path) -> Self {
         self.name = "rename".to_string();
         self.file_name = format!("{}.on", self.name);
-        self.file_path = config.dir.join(&self.file_name);
+        self.file_path = dir.join(&self.file_name);
         ReadParseFile::new(&self.file_path).split_rename(self)
     }
 }
@@ -46,14 +85,16 @@ impl Config {
             folders: Folders::new(),
             types: Types::new(),
             rename: Rename::new(),
+            formats: Formats::new(),
         }
     }
 
-    fn setup(mut self, sphere: &Sphere) -> Self {
-        self.dir = sphere.current_dir.join("config");
-        self.folders = self.folders.clone().setup(&self);
-        self.types = self.types.clone().setup(&self);
-        self.rename = self.rename.clone().setup(&self, sphere);
+    fn setup(mut self, dir: &Path) -> Self {
+        self.dir = dir.to_path_buf();
+        self.folders = Folders::new().setup(&self.dir);
+        self.types = Types::new().setup(&self.dir);
+        self.rename = Rename::new().setup(&self.dir);
+        self.formats = Formats::new().setup(&self.dir);
         self
     }
 }
@@ -63,6 +104,7 @@ pub struct Config {
     pub folders: Folders,
     pub rename: Rename,
     pub types: Types,
+    pub formats: Formats,
 }
 
 #[derive(Clone)]
@@ -84,10 +126,10 @@ impl Folders {
             list: Vec::new(),
         }
     }
-    fn setup(mut self, config: &Config) -> Self {
+    fn setup(mut self, dir: &Path) -> Self {
         self.name = "folders".to_string();
         self.file_name = format!("{}.on", self.name);
-        self.file_path = config.dir.join(&self.file_name);
+        self.file_path = dir.join(&self.file_name);
         self.list = self.readparse();
         self
     }
@@ -109,15 +151,77 @@ impl Types {
             list: Vec::new(),
         }
     }
-    fn setup(mut self, config: &Config) -> Self {
+    fn setup(mut self, dir: &Path) -> Self {
         self.name = "types".to_string();
         self.file_name = format!("{}.on", self.name);
-        self.file_path = config.dir.join(&self.file_name);
+        self.file_path = dir.join(&self.file_name);
         self.list = ReadParseFile::new(&self.file_path).split_whitespace();
         self
     }
 }
 
+/// Build formats of every tipo, from the optional `formats.on` where each
+/// line looks like `<tipo>: ranked json` and `*` stands for every other tipo.
+#[derive(Clone)]
+pub struct Formats {
+    name: String,
+    file_name: String,
+    file_path: PathBuf,
+    pub store: HashMap<String, Vec<BuildFormat>>,
+}
+impl Default for Formats {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+impl Formats {
+    pub fn new() -> Self {
+        Self {
+            name: String::new(),
+            file_name: String::new(),
+            file_path: PathBuf::new(),
+            store: HashMap::new(),
+        }
+    }
+    fn setup(mut self, dir: &Path) -> Self {
+        self.name = "formats".to_string();
+        self.file_name = format!("{}.on", self.name);
+        self.file_path = dir.join(&self.file_name);
+        let content = fs::read_to_string(&self.file_path).unwrap_or_default();
+        self.store = Formats::parse(&content).store;
+        self
+    }
+    /// Skips `//` comments, unknown formats are reported and left out.
+    pub fn parse(content: &str) -> Self {
+        let mut formats = Formats::new();
+        for line in content.split('\n').map(|line| line.trim()) {
+            if line.starts_with("//") {
+                continue;
+            }
+            let (tipo, list) = match line.split_once(':') {
+                Some((tipo, list)) if !tipo.trim().is_empty() => (tipo.trim(), list),
+                _ => continue,
+            };
+            let list: Vec<BuildFormat> = list
+                .split_whitespace()
+                .filter_map(|name| name.parse().map_err(|e| eprintln!("{}", e)).ok())
+                .collect();
+            if !list.is_empty() {
+                formats.store.insert(tipo.to_owned(), list);
+            }
+        }
+        formats
+    }
+    /// Formats `tipo` is built in, `plain` unless `formats.on` says otherwise.
+    pub fn of(&self, tipo: &str) -> Vec<BuildFormat> {
+        self.store
+            .get(tipo)
+            .or_else(|| self.store.get("*"))
+            .cloned()
+            .unwrap_or(vec![BuildFormat::Plain])
+    }
+}
+
 pub struct ReadParseFile {
     pub content: String,
 }
@@ -128,60 +232,50 @@ impl ReadParseFile {
         Self { content }
     }
 
-    pub fn split_mas(self, mas: Mas) -> Vec<Mas> {
-        self.content
-            .split("\n")
-            .enumerate()
-            .map(|(index, word)| {
-                let mut new_mas = mas.clone();
-                new_mas.line = index + 1;
-                new_mas.word = word.trim().to_owned();
-                new_mas
-            })
-            .filter(|mas| mas.word.len() > 0)
-            .collect()
-    }
-
     pub fn split_whitespace(&self) -> Vec<String> {
         self.content
             .split_whitespace()
             .map(|line| line.trim())
-            .filter(|line| line.len() > 0)
+            .filter(|line| !line.is_empty())
             .map(|line| line.to_owned())
             .collect()
     }
 
+    /// Leading `//` lines are comments, every other line must look like
+    /// `from: <tipo> to: <tipo>`, lines that do not go to `malformed`.
     pub fn split_rename(self, mut rename: Rename) -> Rename {
         let list: Vec<_> = self
             .content
-            .split("\n")
+            .split('\n')
             .map(|line| line.trim())
-            .filter(|line| line.len() > 0)
+            .filter(|line| !line.is_empty())
             .collect();
         let comments: Vec<String> = list
             .iter()
-            .take_while(|line| &line[0..2] == "//")
+            .take_while(|line| line.starts_with("//"))
             .map(|line| line.to_string())
             .collect();
-        let rest: Vec<_> = list
-            .iter()
-            .skip(comments.len())
-            .map(|line| line.to_owned())
-            .collect();
 
-        let changes: Vec<_> = rest
-            .iter()
-            .map(|line| {
-                let inner: Vec<_> = line.split("to:").collect();
-                Change {
-                    from: inner[0][5..].trim().to_owned(),
-                    to: inner[1].trim().to_owned(),
-                }
+        let change = |line: &str| {
+            let (from, to) = line.strip_prefix("from:")?.split_once("to:")?;
+            let (from, to) = (from.trim(), to.trim());
+            (!from.is_empty() && !to.is_empty()).then(|| Change {
+                from: from.to_owned(),
+                to: to.to_owned(),
             })
-            .collect();
+        };
+        let mut changes = vec![];
+        let mut malformed = vec![];
+        for line in list.iter().skip(comments.len()) {
+            match change(line) {
+                Some(change) => changes.push(change),
+                None => malformed.push(line.to_string()),
+            }
+        }
 
         rename.comments = comments;
         rename.changes = changes;
+        rename.malformed = malformed;
         rename
     }
 }
@@ -189,18 +283,28 @@ impl ReadParseFile {
 #[derive(Clone, Debug)]
 pub struct Mas {
     pub line: usize,
-    pub word: String,
+    pub word: Word,
     pub tipo: String,
     pub folder: String,
+    pub rank: Option<usize>,
+    pub level: Option<Level>,
+}
+
+impl Default for Mas {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl Mas {
     pub fn new() -> Self {
         Self {
             line: 0,
-            word: String::new(),
+            word: Word::default(),
             tipo: String::new(),
             folder: String::new(),
+            rank: None,
+            level: None,
         }
     }
 
@@ -211,73 +315,22 @@ impl Mas {
     }
 }
 
-#[derive(Clone)]
-pub struct File {
-    pub ext: String,
-    pub name: String,
-    pub full_name: String,
-    pub path: PathBuf,
-}
-impl File {
-    fn new() -> Self {
-        Self {
-            ext: String::new(),
-            name: String::new(),
-            full_name: String::new(),
-            path: PathBuf::new(),
-        }
-    }
-    fn setup(mut self, name: &String, carrier: &Carrier) -> Self {
-        self.name = name.to_owned();
-        self.ext = "on".to_owned();
-        self.full_name = format!("{}.{}", &self.name, &self.ext);
-        self.path = carrier.dir.join(&self.full_name);
-        self
-    }
-}
-#[derive(Clone)]
-pub struct Carrier {
-    pub dir: PathBuf,
-    pub folder: String,
-    pub paths: Vec<File>,
-}
-impl Carrier {
-    fn new() -> Self {
-        Self {
-            dir: PathBuf::new(),
-            folder: String::new(),
-            paths: Vec::new(),
-        }
-    }
-    fn get_data_paths(&self, types: &Vec<String>) -> Vec<File> {
-        let mut acc = Vec::new();
-        for tipo in types {
-            acc.push(File::new().clone().setup(tipo, &self));
-        }
-        acc
-    }
-    fn setup(mut self, voc: &Vocabulary, types: &Vec<String>, folder: &String) -> Self {
-        self.folder = folder.to_owned();
-        self.dir = voc.dir.join(folder);
-        self.paths = self.get_data_paths(types);
-        self
-    }
-}
-
-#[derive(Clone)]
+/// Every word is owned once by `data_all`, in folder, tipo and line order.
+/// `data` and `core_data` only hold positions into it, use `data()` and
+/// `core_data()` to walk them. Words are read from and written to `store`.
 pub struct Vocabulary {
     pub name: String,
     pub dir: PathBuf,
-    pub carrier: Vec<Carrier>,
-    pub data: HashMap<String, Vec<Mas>>,
-    pub data_all: Vec<Mas>,
-    pub core_data: HashMap<String, HashMap<String, Vec<Mas>>>,
+    pub store: Box<dyn VocabularyStore>,
+    data_all: Vec<Mas>,
+    data: Index,
+    core_data: Layout,
 }
 impl Vocabulary {
     fn new() -> Self {
         Self {
             name: String::new(),
-            carrier: Vec::new(),
+            store: Box::new(MemoryStore::default()),
             data: HashMap::new(),
             dir: PathBuf::new(),
             data_all: Vec::new(),
@@ -285,65 +338,159 @@ impl Vocabulary {
         }
     }
 
-    fn read_files_data(
-        &self,
-    ) -> (
-        HashMap<String, Vec<Mas>>,
-        HashMap<String, HashMap<String, Vec<Mas>>>,
-    ) {
-        let mut acc = HashMap::new();
-        let mut layout_data: HashMap<String, HashMap<String, Vec<Mas>>> = HashMap::new();
-        for carrier in &self.carrier {
-            let a = layout_data
-                .entry(carrier.folder.to_owned())
-                .or_insert_with(|| HashMap::new());
-
-            for file in &carrier.paths {
-                let b = a.entry(file.name.clone()).or_insert_with(|| Vec::new());
-
-                let mas = Mas::new().setup(&carrier.folder, &file.name);
-                for mas in ReadParseFile::new(&file.path).split_mas(mas) {
-                    let inner = acc.entry(mas.tipo.to_owned()).or_insert(Vec::new());
-                    b.push(mas.clone());
-                    inner.push(mas);
+    /// Loads every word of `store`.
+    pub fn from_store(store: Box<dyn VocabularyStore>) -> Self {
+        let mut vocabulary = Vocabulary::new();
+        vocabulary.store = store;
+        vocabulary.reload();
+        vocabulary
+    }
+
+    /// Reads the words and levels of `store` again.
+    pub fn reload(&mut self) {
+        self.data_all = self.read_files_data();
+        self.index();
+    }
+
+    fn read_files_data(&self) -> Vec<Mas> {
+        let mut acc = Vec::new();
+        let levels = level::parse(&self.store.read_levels());
+        for folder in self.store.folders() {
+            for tipo in self.store.types() {
+                for (index, word) in self.store.read(&folder, &tipo).iter().enumerate() {
+                    if word.is_empty() {
+                        continue;
+                    }
+                    let word = normal::nfc(word);
+                    let mut mas = Mas::new().setup(&folder, &tipo);
+                    mas.line = index + 1;
+                    mas.word = Word::new(&word);
+                    if let Some((rank, level)) = levels.get(&word) {
+                        mas.rank = Some(*rank);
+                        mas.level = Some(*level);
+                    }
+                    acc.push(mas);
                 }
             }
         }
-        (acc, layout_data)
+        acc
     }
 
-    fn create_folders_file_no_exists(&self) {
-        for carrier in &self.carrier {
-            if !carrier.dir.exists() {
-                fs::create_dir(&carrier.dir).unwrap();
-            }
-            for file in &carrier.paths {
-                if !file.path.exists() {
-                    fs::File::create(&file.path).unwrap();
-                }
+    /// Rebuilds the `data` and `core_data` positions from `data_all`.
+    fn index(&mut self) {
+        self.data.clear();
+        self.core_data.clear();
+        let tipos = self.store.types();
+        for folder in self.store.folders() {
+            let types = self.core_data.entry(folder).or_default();
+            for tipo in &tipos {
+                types.entry(tipo.to_owned()).or_default();
+                self.data.entry(tipo.to_owned()).or_default();
             }
         }
+        for (position, mas) in self.data_all.iter().enumerate() {
+            self.data
+                .entry(mas.tipo.to_owned())
+                .or_default()
+                .push(position);
+            self.core_data
+                .entry(mas.folder.to_owned())
+                .or_default()
+                .entry(mas.tipo.to_owned())
+                .or_default()
+                .push(position);
+        }
+    }
+
+    fn view(&self, positions: &[usize]) -> Vec<&Mas> {
+        positions.iter().map(|n| &self.data_all[*n]).collect()
+    }
+
+    /// Words grouped by tipo.
+    pub fn data(&self) -> impl Iterator<Item = (&String, Vec<&Mas>)> {
+        self.data
+            .iter()
+            .map(|(tipo, positions)| (tipo, self.view(positions)))
+    }
+
+    /// Words of `<folder>/<tipo>.on`, in line order.
+    pub fn core_data(&self, folder: &str, tipo: &str) -> Vec<&Mas> {
+        self.core_data
+            .get(folder)
+            .and_then(|types| types.get(tipo))
+            .map_or(vec![], |positions| self.view(positions))
+    }
+
+    /// Every word, in folder, tipo and line order.
+    pub fn words(&self) -> &[Mas] {
+        &self.data_all
+    }
+
+    pub fn push(&mut self, mas: Mas) {
+        let position = self.data_all.len();
+        self.data
+            .entry(mas.tipo.to_owned())
+            .or_default()
+            .push(position);
+        self.core_data
+            .entry(mas.folder.to_owned())
+            .or_default()
+            .entry(mas.tipo.to_owned())
+            .or_default()
+            .push(position);
+        self.data_all.push(mas);
+    }
+
+    pub fn level(&self, word: &str) -> Option<Level> {
+        self.data_all
+            .iter()
+            .find(|mas| mas.word == word)
+            .and_then(|mas| mas.level)
     }
 
-    fn get_carrier(&self, sphere: &Sphere) -> Vec<Carrier> {
-        let mut acc = vec![];
-        for folder in &sphere.config.folders.list {
-            let types = &sphere.config.types.list;
-            let carrier = Carrier::new().setup(&self, &types, folder);
-            acc.push(carrier);
+    /// Words whose level is `max` or easier, unranked words are left out.
+    pub fn up_to_level(&self, max: Level) -> Vec<&Mas> {
+        self.data_all
+            .iter()
+            .filter(|mas| mas.level.is_some_and(|level| level <= max))
+            .collect()
+    }
+
+    /// Words that only differ by case or accents, like "esta" and "está".
+    /// They may well be different words, so they are only reported.
+    pub fn near_duplicates(&self) -> Vec<Vec<&Mas>> {
+        let mut store: HashMap<&str, Vec<&Mas>> = HashMap::new();
+        for mas in &self.data_all {
+            store.entry(mas.word.as_str()).or_default().push(mas);
         }
-        acc
+        let words = store.keys().map(|word| word.to_string());
+        normal::near_duplicates(words)
+            .iter()
+            .map(|group| {
+                group
+                    .iter()
+                    .flat_map(|word| &store[word.as_str()])
+                    .copied()
+                    .collect()
+            })
+            .collect()
     }
 
-    fn copy_data_into_array(&self) -> Vec<Mas> {
-        let mut acc = vec![];
-        for (_, data) in &self.data {
-            for mas in data {
-                acc.push(mas.clone());
+    /// Ranks every vocabulary word found in the corpus `store` and persists
+    /// the result next to the word files.
+    pub fn assign_levels(&mut self, store: &HashMap<String, (usize, String)>) {
+        let ranks = level::ranks(store);
+        let mut levels = HashMap::new();
+        for mas in &mut self.data_all {
+            mas.rank = ranks.get(mas.word.as_str()).copied();
+            mas.level = mas.rank.map(Level::from_rank);
+            if let (Some(rank), Some(level)) = (mas.rank, mas.level) {
+                levels.insert(mas.word.to_string(), (rank, level));
             }
         }
-        acc
+        self.store.write_levels(&level::content(&levels));
     }
+
     fn get_name(&self, name: &str) -> String {
         let new_name = if name == "aparter" {
             "aparter/vocabulary"
@@ -351,20 +498,15 @@ impl Vocabulary {
             "vocabulary"
         };
 
-        format!("{}", new_name)
+        new_name.to_string()
     }
 
     fn setup(mut self, sphere: &Sphere, name: &str) -> Self {
-        // let compose = format!("{}", name);
-
         self.name = self.get_name(name);
         self.dir = sphere.current_dir.join(&self.name);
-        self.carrier = self.get_carrier(&sphere);
-        self.create_folders_file_no_exists();
-        let together = self.read_files_data();
-        self.data = together.0;
-        self.core_data = together.1;
-        self.data_all = self.copy_data_into_array();
+        let store = DirStore::new(&self.dir).config(&sphere.config.dir).create();
+        self.store = Box::new(store);
+        self.reload();
         self
     }
 }
@@ -372,19 +514,24 @@ impl Vocabulary {
 pub fn extract_forbid_words(sphere: &Sphere) -> Vec<String> {
     sphere
         .vocabulary
-        .data_all
+        .words()
         .iter()
         .map(|mas| mas.word.to_string())
         .collect()
 }
 
-#[derive(Clone)]
 pub struct Sphere {
     pub current_dir: PathBuf,
     pub config: Config,
     pub vocabulary: Vocabulary,
 }
 
+impl Default for Sphere {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl Sphere {
     pub fn new() -> Self {
         Self {
@@ -393,43 +540,69 @@ impl Sphere {
             vocabulary: Vocabulary::new(),
         }
     }
-    pub fn rename_paths(&self) -> Vec<(PathBuf, PathBuf)> {
-        let mut acc = Vec::new();
-        for folder in &self.config.folders.list {
-            let folder_dir = self.vocabulary.dir.join(folder);
-            for change in &self.config.rename.changes {
-                let old_path = folder_dir.join(format!("{}.on", change.from));
-                let new_path = folder_dir.join(format!("{}.on", change.to));
-                acc.push((old_path, new_path));
-            }
+    /// Folders a vocabulary change can touch, for the journal.
+    pub fn paths(&self) -> Vec<PathBuf> {
+        vec![self.vocabulary.dir.to_owned(), self.config.dir.to_owned()]
+    }
+    /// Applies the pending renames, only the comments and the malformed
+    /// lines of `rename.on` are kept afterwards.
+    pub fn rename_files(&mut self) {
+        let changes = std::mem::take(&mut self.config.rename.changes);
+        for line in &self.config.rename.malformed {
+            eprintln!(
+                "{}: ({}) is not a rename, use from: <tipo> to: <tipo>",
+                self.config.rename.file_path.display(),
+                line
+            );
         }
-        acc
-    }
-
-    pub fn rename_files(&self) {
-        for (old_path, new_path) in self.rename_paths() {
-            fs::rename(&old_path, &new_path).unwrap();
+        let paths = self.paths();
+        journal::journal().record("rename", &paths, || {
+            for change in &changes {
+                self.vocabulary.store.rename(&change.from, &change.to);
+            }
+            if self.config.dir.is_dir() {
+                let rename = &self.config.rename;
+                let content: Vec<&str> = rename
+                    .comments
+                    .iter()
+                    .chain(&rename.malformed)
+                    .map(String::as_str)
+                    .collect();
+                fs::write(&rename.file_path, content.join("\n")).unwrap();
+            }
+        });
+        if !changes.is_empty() {
+            self.vocabulary.reload();
         }
-        let comments = self.config.rename.comments.join("\n");
-        fs::write(&self.config.rename.file_path, comments).unwrap();
     }
 
-    pub fn mas(self, name: &str) -> Self {
-        for (tipo, list) in &self.vocabulary.data {
+    pub fn mas(&self, name: &str, full: bool, on_missing: mas::Missing) -> Result<(), mas::Error> {
+        for (tipo, list) in self.vocabulary.data() {
             println!("{}: {} ", tipo, list.len());
         }
-        let acc = extract_forbid_words(&self);
+        let acc: Vec<Word> = self.vocabulary.words().iter().map(|mas| mas.word).collect();
         println!("Vocabulary: {:?}", &acc.len());
-        mas::Mas::new().setup(name).start(acc);
-        self
+        mas::Mas::new()
+            .setup(name)
+            .full(full)
+            .on_missing(on_missing)
+            .start(acc)?;
+        Ok(())
     }
 
     pub fn setup(mut self, name: &str) -> Self {
         println!("VOCABBULARY Running...\n");
         self.current_dir = env::current_dir().unwrap();
-        self.config = self.config.clone().setup(&self);
-        self.vocabulary = self.vocabulary.clone().setup(&self, name);
+        self.config = Config::new().setup(&self.current_dir.join("config"));
+        self.vocabulary = Vocabulary::new().setup(&self, name);
         self.rename_files();
+        for group in self.vocabulary.near_duplicates() {
+            let list: Vec<String> = group
+                .iter()
+                .map(|mas| format!("{} {}/{}.on:{}", mas.word, mas.folder, mas.tipo, mas.line))
+                .collect();
+            println!("({}) only differ by case or accents", list.join(", "));
+        }
 
         for name in ["release", "build"] {
             let release_dir = Path::new(name);
@@ -441,3 +614,84 @@ impl Sphere {
         self
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+    fn mas(word: &str, tipo: &str) -> Mas {
+        let mut mas = Mas::new().setup(&token::folder(word), &tipo.to_owned());
+        mas.word = Word::new(word);
+        mas
+    }
+    #[test]
+    fn views_test() {
+        let mut vocabulary = Vocabulary::new();
+        for (word, tipo) in [("casa", "ANY"), ("perro", "ANY"), ("gato", "esencial")] {
+            vocabulary.push(mas(word, tipo));
+        }
+        let words =
+            |list: Vec<&Mas>| -> Vec<String> { list.iter().map(|n| n.word.to_string()).collect() };
+        assert_eq!(words(vocabulary.core_data("4", "ANY")), vec!["casa"]);
+        assert_eq!(
+            words(vocabulary.core_data("5", "esencial")),
+            Vec::<String>::new()
+        );
+        let mut data: Vec<_> = vocabulary
+            .data()
+            .map(|(tipo, list)| (tipo.to_owned(), words(list)))
+            .collect();
+        data.sort();
+        assert_eq!(
+            data,
+            vec![
+                (
+                    "ANY".to_owned(),
+                    vec!["casa".to_owned(), "perro".to_owned()]
+                ),
+                ("esencial".to_owned(), vec!["gato".to_owned()]),
+            ]
+        );
+    }
+    #[test]
+    fn near_duplicates_test() {
+        let vocabulary = fixture::Fixture::new()
+            .types(&["ANY", "esencial"])
+            .words("ANY", &["esta", "casa"])
+            .line("5", "esencial", "está")
+            .line("7", "ANY", "cancio\u{301}n")
+            .vocabulary();
+        assert_eq!(vocabulary.core_data("7", "ANY")[0].word, "canción");
+        let groups: Vec<Vec<String>> = vocabulary
+            .near_duplicates()
+            .iter()
+            .map(|group| group.iter().map(|mas| mas.folder.to_owned()).collect())
+            .collect();
+        assert_eq!(groups, vec![vec!["4", "5"]]);
+    }
+    #[test]
+    fn split_rename_test() {
+        let content = "// é\na\né\nfrom: ANY\nto: ANY\nfrom: ANY to: esencial".to_owned();
+        let rename = ReadParseFile { content }.split_rename(Rename::new());
+        assert_eq!(rename.comments, vec!["// é"]);
+        assert_eq!(rename.changes.len(), 1);
+        assert_eq!(rename.changes[0].from, "ANY");
+        assert_eq!(rename.changes[0].to, "esencial");
+        assert_eq!(rename.malformed, vec!["a", "é", "from: ANY", "to: ANY"]);
+    }
+    proptest! {
+        #[test]
+        fn split_rename_prop(content in "\\PC*(\n\\PC*)*") {
+            ReadParseFile { content }.split_rename(Rename::new());
+        }
+        #[test]
+        fn split_rename_change_prop(from in "[a-zA-Z_]{1,12}", to in "[a-zA-Z_]{1,12}") {
+            let content = format!("// rename\nfrom: {} to: {}\n", from, to);
+            let rename = ReadParseFile { content }.split_rename(Rename::new());
+            prop_assert_eq!(rename.comments, vec!["// rename".to_owned()]);
+            prop_assert_eq!(rename.changes.len(), 1);
+            prop_assert_eq!(&rename.changes[0].from, &from);
+            prop_assert_eq!(&rename.changes[0].to, &to);
+        }
+    }
+}
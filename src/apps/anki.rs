@@ -0,0 +1,159 @@
+use std::collections::{BTreeMap, HashMap};
+
+use crate::apps::{booktore, checksum, Mas, Vocabulary};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DeckBy {
+    Tipo,
+    Length,
+}
+
+impl DeckBy {
+    fn deck(&self, mas: &Mas) -> String {
+        match self {
+            DeckBy::Tipo => mas.tipo.to_owned(),
+            DeckBy::Length => mas.folder.to_owned(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Card {
+    pub guid: String,
+    pub front: String,
+    pub back: String,
+    pub examples: Vec<String>,
+    pub tags: Vec<String>,
+}
+
+impl Card {
+    /// A card of the `deck` deck. `store` is the booktore corpus, as
+    /// returned by `init_get_system`.
+    pub fn new(mas: &Mas, deck: &str, store: &HashMap<String, (usize, String)>) -> Self {
+        let mut back = vec![mas.tipo.to_owned(), format!("{} letras", mas.folder)];
+        let mut tags = vec![
+            format!("folder::{}", mas.folder),
+            format!("tipo::{}", mas.tipo),
+        ];
+        if let Some(level) = mas.level {
+            back.push(level.to_string());
+            tags.push(format!("level::{}", level));
+        }
+        let examples = store
+            .get(mas.word.as_str())
+            .map_or(vec![], |(_, sentences)| booktore::examples(sentences));
+
+        Self {
+            guid: guid(deck, &mas.tipo, &mas.word),
+            front: mas.word.to_string(),
+            back: back.join(" · "),
+            examples,
+            tags,
+        }
+    }
+
+    fn line(&self) -> String {
+        let examples: Vec<_> = self.examples.iter().map(|e| escape(e)).collect();
+        [
+            self.guid.to_owned(),
+            escape(&self.front),
+            escape(&self.back),
+            examples.join("<br>"),
+            self.tags.join(" "),
+        ]
+        .join("\t")
+    }
+}
+
+/// Stable identifier so re-importing a deck updates cards instead of
+/// duplicating them. The same word in another deck or tipo is another card.
+pub fn guid(deck: &str, tipo: &str, word: &str) -> String {
+    let key = format!("{}:{}:{}", deck, tipo, word);
+    format!("gamo-{}", checksum::hex(key.as_bytes()))
+}
+
+fn escape(input: &str) -> String {
+    input
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace(['\t', '\n'], " ")
+}
+
+pub fn decks(
+    vocabulary: &Vocabulary,
+    store: &HashMap<String, (usize, String)>,
+    deck_by: DeckBy,
+) -> BTreeMap<String, Vec<Card>> {
+    let mut list: Vec<_> = vocabulary.words().iter().collect();
+    list.sort_by(|a, b| {
+        (a.rank.unwrap_or(usize::MAX), &a.word).cmp(&(b.rank.unwrap_or(usize::MAX), &b.word))
+    });
+    let mut acc: BTreeMap<String, Vec<Card>> = BTreeMap::new();
+    for mas in list {
+        let deck = deck_by.deck(mas);
+        let card = Card::new(mas, &deck, store);
+        acc.entry(deck).or_default().push(card);
+    }
+    acc
+}
+
+/// Tab separated deck with the file headers understood by Anki's importer.
+pub fn tsv(deck: &str, cards: &[Card]) -> String {
+    let header = [
+        "#separator:tab".to_owned(),
+        "#html:true".to_owned(),
+        "#columns:guid\tfront\tback\texamples\ttags".to_owned(),
+        "#guid column:1".to_owned(),
+        "#tags column:5".to_owned(),
+        format!("#deck:gamo::{}", deck),
+    ];
+    let mut list: Vec<String> = header.to_vec();
+    list.extend(cards.iter().map(|card| card.line()));
+    format!("{}\n", list.join("\n"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::apps::{intern::Word, level::Level, token};
+    fn mas(word: &str) -> Mas {
+        let mut mas = Mas::new();
+        mas.word = Word::new(word);
+        mas.tipo = "esencial".to_owned();
+        mas.folder = token::folder(word);
+        mas
+    }
+    #[test]
+    fn guid_test() {
+        assert_eq!(guid("4", "ANY", "casa"), guid("4", "ANY", "casa"));
+        assert_ne!(guid("4", "ANY", "casa"), guid("4", "ANY", "cosa"));
+        assert_ne!(guid("4", "ANY", "casa"), guid("4", "esencial", "casa"));
+        assert_ne!(guid("ANY", "ANY", "casa"), guid("4", "ANY", "casa"));
+    }
+    #[test]
+    fn card_test() {
+        let mut store = HashMap::new();
+        store.insert(
+            "casa".to_owned(),
+            (2, "la, casa grande \nmi, casa <roja> \n".to_owned()),
+        );
+        let mut casa = mas("casa");
+        casa.level = Some(Level::A1);
+        let card = Card::new(&casa, "esencial", &store);
+        assert_eq!(card.back, "esencial · 4 letras · A1");
+        assert_eq!(card.tags, vec!["folder::4", "tipo::esencial", "level::A1"]);
+        assert_eq!(card.examples.len(), 2);
+        let line = card.line();
+        assert_eq!(line.split('\t').count(), 5);
+        assert!(line.contains("mi, casa &lt;roja&gt;"));
+    }
+    #[test]
+    fn tsv_test() {
+        let card = Card::new(&mas("perro"), "esencial", &HashMap::new());
+        let content = tsv("esencial", &[card]);
+        assert!(content.starts_with("#separator:tab\n"));
+        assert!(content.contains("#deck:gamo::esencial\n"));
+        assert!(content.ends_with("tipo::esencial\n"));
+    }
+}
@@ -0,0 +1,362 @@
+use serde_json::{json, Value};
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    fs,
+    path::{Path, PathBuf},
+};
+
+use crate::apps::{
+    booktore::Corpus, case::Casing, import::Import, normal, off::OffFile, token, Mas, Sphere,
+};
+
+const WIDTH: usize = 5;
+const MAX_WIDTH: usize = 50;
+const LIMIT: usize = 20;
+const MAX_LIMIT: usize = 500;
+
+/// A JSON answer and its HTTP status.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Response {
+    pub status: u16,
+    pub body: String,
+}
+
+impl Response {
+    fn ok(value: Value) -> Self {
+        Self::status(200, value)
+    }
+
+    fn status(status: u16, value: Value) -> Self {
+        Self {
+            status,
+            body: format!("{}\n", value),
+        }
+    }
+
+    fn error(status: u16, message: &str) -> Self {
+        Self::status(status, json!({ "error": message }))
+    }
+}
+
+/// `%xx` escapes and `+` of a path segment or query value.
+fn decode(input: &str) -> Option<String> {
+    let mut bytes = vec![];
+    let mut list = input.bytes();
+    while let Some(byte) = list.next() {
+        match byte {
+            b'%' => {
+                let hex = [list.next()?, list.next()?];
+                let hex = std::str::from_utf8(&hex).ok()?;
+                bytes.push(u8::from_str_radix(hex, 16).ok()?);
+            }
+            b'+' => bytes.push(b' '),
+            byte => bytes.push(byte),
+        }
+    }
+    String::from_utf8(bytes).ok()
+}
+
+fn parse_query(input: &str) -> Option<HashMap<String, String>> {
+    let mut acc = HashMap::new();
+    for pair in input.split('&').filter(|n| !n.is_empty()) {
+        let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+        acc.insert(decode(key)?, decode(value)?);
+    }
+    Some(acc)
+}
+
+fn entry(mas: &Mas) -> Value {
+    json!({
+        "word": mas.word.as_str(),
+        "folder": mas.folder,
+        "tipo": mas.tipo,
+        "line": mas.line,
+        "level": mas.level.map(|level| level.to_string()),
+    })
+}
+
+/// JSON endpoints over a workspace, without any HTTP: `serve` only moves
+/// requests and responses in and out of [`Api::handle`].
+///
+/// - `GET /vocabulary`, folders, types and the word count.
+/// - `GET /vocabulary/<folder>/<tipo>`, the words of one list.
+/// - `GET /words/<word>`, where the word is filed, its corpus frequency and
+///   examples, and the reason it was rejected.
+/// - `GET /concordance/<word>?width=5&limit=20`, keyword in context.
+/// - `GET /candidates`, the part files of the last `mas` run that are still
+///   undecided.
+/// - `POST /candidates/<word>/off?reason=&section=`, rejects a candidate.
+/// - `POST /candidates/<word>/vocabulary?tipo=`, imports a candidate.
+pub struct Api<'a> {
+    pub sphere: Sphere,
+    /// Directory of `word.off` and the `parts` of `mas`.
+    root: PathBuf,
+    corpus: &'a Corpus,
+}
+
+impl<'a> Api<'a> {
+    pub fn new<T: AsRef<Path>>(sphere: Sphere, root: T, corpus: &'a Corpus) -> Self {
+        Self {
+            sphere,
+            root: root.as_ref().to_path_buf(),
+            corpus,
+        }
+    }
+
+    pub fn handle(&mut self, method: &str, url: &str) -> Response {
+        let (path, query) = url.split_once('?').unwrap_or((url, ""));
+        let segments: Option<Vec<String>> = path
+            .split('/')
+            .filter(|n| !n.is_empty())
+            .map(decode)
+            .collect();
+        let (segments, query) = match (segments, parse_query(query)) {
+            (Some(segments), Some(query)) => (segments, query),
+            _ => return Response::error(400, "malformed url"),
+        };
+        let segments: Vec<&str> = segments.iter().map(|n| n.as_str()).collect();
+        match (method, segments.as_slice()) {
+            ("GET", ["vocabulary"]) => self.summary(),
+            ("GET", ["vocabulary", folder, tipo]) => self.list(folder, tipo),
+            ("GET", ["words", word]) => self.word(word),
+            ("GET", ["concordance", word]) => self.concordance(word, &query),
+            ("GET", ["candidates"]) => self.candidates(),
+            ("POST", ["candidates", word, "off"]) => self.reject(word, &query),
+            ("POST", ["candidates", word, "vocabulary"]) => self.import(word, &query),
+            (_, ["vocabulary"] | ["vocabulary", _, _] | ["words", _] | ["concordance", _])
+            | (_, ["candidates"] | ["candidates", _, "off" | "vocabulary"]) => {
+                Response::error(405, "method not allowed")
+            }
+            _ => Response::error(404, "not found"),
+        }
+    }
+
+    fn off(&self) -> OffFile {
+        OffFile::read(self.root.join("word.off"))
+    }
+
+    fn summary(&self) -> Response {
+        let store = &self.sphere.vocabulary.store;
+        Response::ok(json!({
+            "folders": store.folders(),
+            "types": store.types(),
+            "words": self.sphere.vocabulary.words().len(),
+        }))
+    }
+
+    fn list(&self, folder: &str, tipo: &str) -> Response {
+        let store = &self.sphere.vocabulary.store;
+        if !store.folders().iter().any(|n| n == folder) || !store.types().iter().any(|n| n == tipo)
+        {
+            return Response::error(404, "unknown folder or tipo");
+        }
+        let list = self.sphere.vocabulary.core_data(folder, tipo);
+        Response::ok(Value::Array(list.into_iter().map(entry).collect()))
+    }
+
+    fn word(&self, word: &str) -> Response {
+        let word = normal::word(word);
+        let entries: Vec<Value> = self
+            .sphere
+            .vocabulary
+            .words()
+            .iter()
+            .filter(|mas| mas.word.to_lowercase() == word)
+            .map(entry)
+            .collect();
+        let frequency = self.corpus.frequency_of(&word);
+        let off = self.off();
+        let rejected = off.words().contains(&word.as_str());
+        if entries.is_empty() && frequency.is_none() && !rejected {
+            return Response::error(404, "unknown word");
+        }
+        Response::ok(json!({
+            "word": word,
+            "entries": entries,
+            "frequency": frequency.map(|n| json!({ "count": n.count, "books": n.books })),
+            "examples": self.corpus.examples_of(&word),
+            "off": rejected,
+            "reason": off.reason(&word),
+        }))
+    }
+
+    fn concordance(&self, word: &str, query: &HashMap<String, String>) -> Response {
+        let number = |key: &str, default: usize, max: usize| match query.get(key) {
+            Some(value) => value.parse::<usize>().ok().filter(|n| *n <= max),
+            None => Some(default),
+        };
+        let (width, limit) = match (
+            number("width", WIDTH, MAX_WIDTH),
+            number("limit", LIMIT, MAX_LIMIT),
+        ) {
+            (Some(width), Some(limit)) => (width, limit),
+            _ => return Response::error(400, "width and limit must be small numbers"),
+        };
+        let list = self.corpus.concordance(&normal::word(word), width, limit);
+        Response::ok(serde_json::to_value(list).unwrap())
+    }
+
+    fn candidates(&self) -> Response {
+        let known: HashSet<String> = self
+            .sphere
+            .vocabulary
+            .words()
+            .iter()
+            .map(|mas| mas.word.to_lowercase())
+            .collect();
+        let off = self.off();
+        let off: HashSet<&str> = off.words().into_iter().collect();
+        let mut parts: BTreeMap<String, Vec<String>> = BTreeMap::new();
+        let dir = fs::read_dir(self.root.join("parts"));
+        for path in dir.into_iter().flatten().flatten().map(|n| n.path()) {
+            if path.extension().is_none_or(|ext| ext != "on") {
+                continue;
+            }
+            let name = path.file_stem().unwrap().to_string_lossy().to_string();
+            let content = fs::read_to_string(&path).unwrap_or_default();
+            let list: Vec<String> = content
+                .split_whitespace()
+                .filter(|word| {
+                    let word = normal::word(word);
+                    !known.contains(&word) && !off.contains(word.as_str())
+                })
+                .map(|word| word.to_owned())
+                .collect();
+            if !list.is_empty() {
+                parts.insert(name, list);
+            }
+        }
+        Response::ok(json!(parts))
+    }
+
+    fn reject(&self, word: &str, query: &HashMap<String, String>) -> Response {
+        let word = match token::word(word) {
+            Some(word) => word,
+            None => return Response::error(400, "not a valid word"),
+        };
+        let field = |key: &str| query.get(key).map(|n| n.as_str()).filter(|n| !n.is_empty());
+        let (reason, section) = (field("reason"), field("section"));
+        let valid = |text: &str| {
+            !text
+                .chars()
+                .any(|letter| letter.is_control() || letter == '[' || letter == ']')
+        };
+        if !reason.into_iter().chain(section).all(valid) {
+            return Response::error(
+                400,
+                "reason and section cannot hold control characters or brackets",
+            );
+        }
+        let mut off = self.off();
+        if !off.reject(&word, reason, section) {
+            return Response::error(409, "already rejected");
+        }
+        off.write();
+        Response::ok(json!({ "word": word, "off": true }))
+    }
+
+    fn import(&mut self, word: &str, query: &HashMap<String, String>) -> Response {
+        let off: Vec<String> = self.off().words().iter().map(|n| n.to_string()).collect();
+        let vocabulary = &mut self.sphere.vocabulary;
+        let tipo = match query.get("tipo") {
+            Some(tipo) if vocabulary.store.types().contains(tipo) => tipo,
+            _ => return Response::error(400, "tipo must be one of the vocabulary types"),
+        };
+        let words = [word.to_owned()];
+        let import = Import::plan(&words, tipo, vocabulary, &off, &Casing::default());
+        import.apply(vocabulary);
+        let skipped: Vec<Value> = import
+            .skipped
+            .iter()
+            .map(|(word, reason)| json!({ "word": word, "reason": reason.to_string() }))
+            .collect();
+        let status = if import.added.is_empty() { 409 } else { 200 };
+        Response::status(
+            status,
+            json!({
+                "added": import.added.iter().map(entry).collect::<Vec<_>>(),
+                "skipped": skipped,
+            }),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::apps::{booktore, fixture::Fixture};
+    fn api(root: &Path, corpus: &'static Corpus) -> Api<'static> {
+        let sphere = Fixture::new()
+            .types(&["ANY", "esencial"])
+            .words("ANY", &["casa", "perro"])
+            .words("esencial", &["gato"])
+            .sphere();
+        Api::new(sphere, root, corpus)
+    }
+    fn json(response: &Response) -> Value {
+        serde_json::from_str(&response.body).unwrap()
+    }
+    #[test]
+    fn decode_test() {
+        assert_eq!(decode("canci%C3%B3n+a"), Some("canción a".to_owned()));
+        assert_eq!(decode("%C3"), None);
+        assert_eq!(decode("%zz"), None);
+    }
+    #[test]
+    fn handle_test() {
+        let root = std::env::temp_dir().join(format!("gamo-api-{}", std::process::id()));
+        fs::create_dir_all(root.join("parts")).unwrap();
+        fs::write(root.join("parts/F-4.on"), "casa\nloro\nmesa\n").unwrap();
+        fs::write(root.join("parts/F-4.off"), "ignored\n").unwrap();
+        let books = ["La casa y el gato.".to_owned(), "Un gato.".to_owned()];
+        let corpus: &'static Corpus = Box::leak(Box::new(booktore::index(&books)));
+        let mut api = api(&root, corpus);
+
+        let response = api.handle("GET", "/vocabulary");
+        assert_eq!(json(&response)["words"], 3);
+        let response = api.handle("GET", "/vocabulary/4/ANY");
+        assert_eq!(json(&response)[0]["word"], "casa");
+        assert_eq!(api.handle("GET", "/vocabulary/9/ANY").status, 404);
+
+        let response = json(&api.handle("GET", "/words/Gato"));
+        assert_eq!(response["entries"][0]["tipo"], "esencial");
+        assert_eq!(response["frequency"]["books"], 2);
+        assert_eq!(api.handle("GET", "/words/nada").status, 404);
+
+        let response = json(&api.handle("GET", "/concordance/gato?width=1"));
+        assert_eq!(response[0]["left"], "el");
+        assert_eq!(response[1]["right"], "");
+        assert_eq!(api.handle("GET", "/concordance/gato?width=x").status, 400);
+
+        assert_eq!(
+            json(&api.handle("GET", "/candidates"))["F-4"],
+            json!(["loro", "mesa"])
+        );
+        let response = api.handle("POST", "/candidates/loro/off?reason=ave");
+        assert_eq!(response.status, 200);
+        assert_eq!(api.handle("POST", "/candidates/loro/off").status, 409);
+        assert_eq!(api.handle("POST", "/candidates/x1/off").status, 400);
+        assert_eq!(
+            api.handle("POST", "/candidates/mesa/off?reason=a%0A%5Bb%5D")
+                .status,
+            400
+        );
+        assert_eq!(
+            api.handle("POST", "/candidates/mesa/off?section=%5Bx")
+                .status,
+            400
+        );
+        assert_eq!(
+            api.handle("POST", "/candidates/mesa/vocabulary").status,
+            400
+        );
+        let response = api.handle("POST", "/candidates/mesa/vocabulary?tipo=ANY");
+        assert_eq!(json(&response)["added"][0]["line"], 2);
+        assert_eq!(json(&api.handle("GET", "/candidates")), json!({}));
+        assert_eq!(json(&api.handle("GET", "/words/loro"))["reason"], "ave");
+
+        assert_eq!(api.handle("DELETE", "/vocabulary").status, 405);
+        assert_eq!(api.handle("GET", "/nada").status, 404);
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
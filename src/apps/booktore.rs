@@ -1,70 +1,23 @@
-use indexmap::IndexMap;
-use std::{self, collections::HashMap, fs, path::Path};
+use crate::apps::case::Casing;
+use crate::apps::intern::Word;
+use crate::apps::normal;
+pub use crate::apps::token::parse_word;
+use crate::apps::token::English;
+use indexmap::{IndexMap, IndexSet};
+use rayon::prelude::*;
+use serde::Serialize;
+use std::{self, collections::HashMap, fs, sync::OnceLock};
+
 static SENTENCE_START: isize = 3;
 static SENTENCE_END: usize = 9;
 static TAKE: usize = 3;
-struct English;
-impl English {
-    fn start() -> i32 {
-        97
-    }
-    fn end() -> i32 {
-        122
-    }
-
-    fn is_range(value: i32) -> bool {
-        value >= English::start() && value <= English::end()
-    }
-    fn valid_english(input: &str) -> bool {
-        for letter in input.chars() {
-            if !English::is_range(letter as i32) {
-                return false;
-            }
-        }
-        input.len() > 0
-    }
-}
-struct Str;
-impl Str {
-    fn rm_start(input: &str) -> &str {
-        let mut start = 0;
-        let mut list = input.chars();
-        let mut current = list.next();
-        while let Some(letter) = current {
-            if !English::is_range(letter as i32) {
-                start += letter.len_utf8();
-            } else {
-                return &input[start..input.len()];
-            }
-            current = list.next();
-        }
-        &input[start..input.len()]
-    }
-    fn rm_end(input: &str) -> &str {
-        let mut end = Str::utf_count(input);
-        let mut list = input.chars();
-        let mut current = list.next_back();
-        while let Some(letter) = current {
-            if !English::is_range(letter as i32) {
-                end -= letter.len_utf8();
-            } else {
-                return &input[0..end];
-            }
-            current = list.next_back();
-        }
-        &input[0..end]
-    }
-    fn rm_start_end(input: &str) -> &str {
-        Str::rm_end(Str::rm_start(input))
-    }
-    fn utf_count(input: &str) -> usize {
-        input.chars().fold(0, |acc, letter| acc + letter.len_utf8())
-    }
+fn join(list: &[Word]) -> String {
+    let list: Vec<&str> = list.iter().map(|word| word.as_str()).collect();
+    list.join(" ")
 }
-
-fn get_sentence_end(index_at: usize, inner: &Inner) -> String {
+fn get_sentence_end(index_at: usize, inner: &Corpus) -> String {
     if index_at >= inner.list.len() {
-        return format!("{}", "");
+        return String::new();
     }
     let mut index_end = index_at + SENTENCE_END;
 
@@ -72,11 +25,11 @@ fn get_sentence_end(index_at: usize, inner: &Inner) -> String {
         index_end -= 1;
     }
 
-    format!("{}", &inner.list[index_at..index_end].join(" "))
+    join(&inner.list[index_at..index_end])
 }
-fn get_sentence_start(index_at: isize, end_at: usize, inner: &Inner) -> String {
+fn get_sentence_start(index_at: isize, end_at: usize, inner: &Corpus) -> String {
     if index_at < 0 {
-        return format!("{}", "");
+        return String::new();
     }
     let mut start_point = index_at - SENTENCE_START;
 
@@ -84,40 +37,190 @@ fn get_sentence_start(index_at: isize, end_at: usize, inner: &Inner) -> String {
         start_point += 1;
     }
 
-    format!("{}", &inner.list[start_point as usize..end_at].join(" "))
+    join(&inner.list[start_point as usize..end_at])
 }
 
-#[derive(Debug)]
+#[derive(Debug, PartialEq)]
 struct Data {
     sentences: Vec<usize>,
 }
-struct Inner {
-    store: IndexMap<String, Data>,
-    list: Vec<String>,
+/// Position index of the corpus: every token in reading order and, for each
+/// parsed word, the positions where it appears. Tokens and words are
+/// interned, repeated tokens cost a pointer each.
+#[derive(Debug, Default, PartialEq)]
+pub struct Corpus {
+    store: IndexMap<Word, Data>,
+    list: Vec<Word>,
+    /// Position of the first token of every book.
+    books: Vec<usize>,
+}
+
+impl Corpus {
+    pub fn tokens(&self) -> usize {
+        self.list.len()
+    }
+    pub fn words(&self) -> usize {
+        self.store.len()
+    }
+    fn book_at(&self, position: usize) -> usize {
+        self.books.partition_point(|start| *start <= position)
+    }
+
+    fn count(&self, data: &Data) -> Frequency {
+        let mut books = data
+            .sentences
+            .iter()
+            .map(|n| self.book_at(*n))
+            .collect::<Vec<_>>();
+        books.dedup();
+        Frequency {
+            count: data.sentences.len(),
+            books: books.len(),
+        }
+    }
+
+    fn data(&self, word: &str) -> Option<&Data> {
+        Word::get(word).and_then(|word| self.store.get(&word))
+    }
+
+    /// [`Frequency`] of one word, `None` when the corpus does not have it.
+    pub fn frequency_of(&self, word: &str) -> Option<Frequency> {
+        self.data(word).map(|data| self.count(data))
+    }
+
+    /// Example sentences of one word, the ones [`examples_of`] gives.
+    pub fn examples_of(&self, word: &str) -> Vec<String> {
+        let data = match self.data(word) {
+            Some(data) => data,
+            None => return vec![],
+        };
+        data.sentences
+            .iter()
+            .take(TAKE)
+            .map(|n| stitch_words_sentences(*n, self).trim().to_owned())
+            .collect()
+    }
+
+    /// Every occurrence of `word` with `width` tokens on each side, at most
+    /// `limit` of them, without crossing into another book.
+    pub fn concordance(&self, word: &str, width: usize, limit: usize) -> Vec<Concordance> {
+        let data = match self.data(word) {
+            Some(data) => data,
+            None => return vec![],
+        };
+        data.sentences
+            .iter()
+            .take(limit)
+            .map(|position| {
+                let book = self.book_at(*position);
+                let start = self.books[book - 1].max(position.saturating_sub(width));
+                let end = self
+                    .books
+                    .get(book)
+                    .map_or(self.list.len(), |n| *n)
+                    .min(position + 1 + width);
+                Concordance {
+                    left: join(&self.list[start..*position]),
+                    word: self.list[*position].to_string(),
+                    right: join(&self.list[position + 1..end]),
+                    book: book - 1,
+                }
+            })
+            .collect()
+    }
 }
 
-pub fn parse_word(word: &str) -> String {
-    let word = word.trim().to_lowercase();
-    Str::rm_start_end(&word).to_owned()
+/// One line of a keyword in context listing.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Concordance {
+    pub left: String,
+    /// The token as it appears in the book.
+    pub word: String,
+    pub right: String,
+    pub book: usize,
+}
+
+/// One book indexed on its own: tokens and parsed words are numbered
+/// locally so threads do not share the interner until [`merge`].
+struct Book<'a> {
+    tokens: IndexSet<&'a str>,
+    /// Every token in reading order, as a position in `tokens`.
+    list: Vec<usize>,
+    store: IndexMap<String, Data>,
 }
 
-fn parse_content(content: String) -> Inner {
+fn parse_content(content: &str) -> Book<'_> {
+    let mut tokens: IndexSet<&str> = IndexSet::new();
     let mut store: IndexMap<String, Data> = IndexMap::new();
-    let list: Vec<String> = content.split_whitespace().map(|n| n.to_owned()).collect();
-
-    for (index, line) in list.clone().into_iter().enumerate() {
-        let word = parse_word(&line);
-        if let Some(data) = store.get_mut(&word) {
-            data.sentences.push(index);
-        } else {
-            let data = Data {
-                sentences: vec![index],
-            };
-            store.insert(word, data);
+    let mut parsed: Vec<usize> = vec![];
+    let mut list = vec![];
+
+    for (index, token) in content.split_whitespace().enumerate() {
+        let (id, new) = tokens.insert_full(token);
+        if new {
+            let entry = store.entry(parse_word(token));
+            parsed.push(entry.index());
+            entry.or_insert(Data { sentences: vec![] });
         }
+        store[parsed[id]].sentences.push(index);
+        list.push(id);
     }
 
-    Inner { store, list }
+    Book {
+        tokens,
+        list,
+        store,
+    }
+}
+
+/// Joins the indexes of consecutive books, shifting their positions so the
+/// result is the index of the books read one after the other. Words are
+/// interned here, once per book.
+fn merge(parts: Vec<Book>) -> Corpus {
+    let mut corpus = Corpus::default();
+    for part in parts {
+        let offset = corpus.list.len();
+        corpus.books.push(offset);
+        let tokens = Word::list(part.tokens.iter().copied());
+        let words = Word::list(part.store.keys().map(|word| word.as_str()));
+        for (word, (_, data)) in words.into_iter().zip(part.store) {
+            let entry = corpus
+                .store
+                .entry(word)
+                .or_insert(Data { sentences: vec![] });
+            entry
+                .sentences
+                .extend(data.sentences.into_iter().map(|n| n + offset));
+        }
+        corpus.list.extend(part.list.into_iter().map(|n| tokens[n]));
+    }
+    corpus
+}
+
+/// Indexes every book on its own thread and merges the results.
+pub fn index(books: &[String]) -> Corpus {
+    merge(books.par_iter().map(|book| parse_content(book)).collect())
+}
+
+/// Single threaded reference for [`index`], both give the same `Corpus`.
+pub fn index_sequential(books: &[String]) -> Corpus {
+    let content = books.join(" ");
+    let mut corpus = merge(vec![parse_content(&content)]);
+    corpus.books = books
+        .iter()
+        .scan(0, |start, book| {
+            let at = *start;
+            *start += book.split_whitespace().count();
+            Some(at)
+        })
+        .collect();
+    corpus
+}
+
+/// The corpus of the public domain books, indexed once per process.
+pub fn corpus() -> &'static Corpus {
+    static CORPUS: OnceLock<Corpus> = OnceLock::new();
+    CORPUS.get_or_init(|| index(&read_books()))
 }
 
 fn compose_sentence(content: String) -> String {
@@ -133,7 +236,7 @@ fn compose_sentence(content: String) -> String {
     list.join(" ")
 }
 
-fn stitch_words_sentences(index: usize, inner: &Inner) -> String {
+fn stitch_words_sentences(index: usize, inner: &Corpus) -> String {
     let left = get_sentence_start(index as isize - 1, index, inner);
     let word = &inner.list[index];
     let right = get_sentence_end(index + 1, inner);
@@ -142,20 +245,6 @@ fn stitch_words_sentences(index: usize, inner: &Inner) -> String {
     format!("{}, {} {} \n", left, word, right)
 }
 
-fn get_popularity_sort(inner: &Inner) -> Vec<(String, &Data)> {
-    let mut acc = vec![];
-    for (word, data) in &inner.store {
-        if English::valid_english(word) && word.len() > 1 {
-            acc.push((word.to_owned(), data));
-        }
-    }
-
-    acc.sort_by(|(_, a), (_, b)| a.sentences.len().cmp(&b.sentences.len()).reverse());
-    let todo: Vec<_> = acc.clone().iter().map(|(w, _)| w.to_owned()).collect();
-    fs::write("palabras.on", todo.join("\n")).unwrap();
-    acc
-}
-
 fn read_public_domain_books() -> Vec<String> {
     let mut acc = Vec::new();
     let custom_dirs = fs_extra::dir::get_dir_content("custom_public_domain");
@@ -164,131 +253,129 @@ fn read_public_domain_books() -> Vec<String> {
         acc = dir_content.files;
     }
 
-    let public_domain = fs_extra::dir::get_dir_content("public_domain")
+    let mut public_domain = fs_extra::dir::get_dir_content("public_domain")
         .unwrap()
         .files;
+    public_domain.sort();
+    acc.sort();
 
-    vec![public_domain, acc].concat()
+    [public_domain, acc].concat()
 }
 
-fn get_public_domain_books() -> String {
-    let mut acc = String::new();
-    for file_name in read_public_domain_books() {
-        if !file_name.contains(".txt") {
-            continue;
-        }
-        let content = fs::read_to_string(file_name).unwrap();
-        let list: Vec<_> = content.split_ascii_whitespace().map(|n| n.trim()).collect();
-
-        let content = list.join(" ");
-        acc.push_str(&content);
-    }
-    acc
+/// Reads the text of every public domain book, in parallel, with its
+/// whitespace collapsed to single spaces.
+pub fn read_books() -> Vec<String> {
+    read_public_domain_books()
+        .into_par_iter()
+        .filter(|file_name| file_name.contains(".txt"))
+        .map(|file_name| {
+            let content = normal::nfc(&fs::read_to_string(file_name).unwrap());
+            let list: Vec<_> = content.split_ascii_whitespace().map(|n| n.trim()).collect();
+            list.join(" ")
+        })
+        .collect()
 }
-fn get_content_single_file() -> Vec<String> {
-    let content = fs::read_to_string("word.on").unwrap();
-    let mut acc = vec![];
-    for word in content.split("\n").map(|n| n.trim()).filter(|n| n != &"") {
-        acc.push(word.to_owned());
-    }
-    acc
+/// Corpus count and example sentences of a set of words. It is built in
+/// memory, `order` is what used to be written to `palabras.on`.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct ExampleStore {
+    pub words: HashMap<String, (usize, String)>,
+    /// The same words, most frequent first.
+    pub order: Vec<String>,
 }
 
-fn get_word_file(inner: &Inner) -> Vec<(String, &Data)> {
-    let mut acc = vec![];
-
-    let list_of_mas_words = get_content_single_file();
-
-    for word in list_of_mas_words {
-        if inner.store.contains_key(&word) {
-            let data = inner.store.get(&word).unwrap();
-            acc.push((word, data));
+impl ExampleStore {
+    fn new(inner: &Corpus, mut acc: Vec<(Word, &Data)>) -> Self {
+        acc.sort_by(|(_, a), (_, b)| a.sentences.len().cmp(&b.sentences.len()).reverse());
+        let mut store = ExampleStore::default();
+        for (word, data) in &acc {
+            let x = data
+                .sentences
+                .iter()
+                .take(TAKE)
+                .fold(String::new(), |mut acc, b| {
+                    acc.push_str(&stitch_words_sentences(*b, inner));
+                    acc
+                });
+
+            let sentence = format!("{}\n", x.trim());
+            store
+                .words
+                .insert(word.to_string(), (data.sentences.len(), sentence));
+            store.order.push(word.to_string());
         }
+        store
     }
-    acc.sort_by(|(_, a), (_, b)| a.sentences.len().cmp(&b.sentences.len()).reverse());
-    let todo: Vec<_> = acc.clone().iter().map(|(w, _)| w.to_owned()).collect();
-    fs::write("palabras.on", todo.join("\n")).unwrap();
-    acc
-}
 
-fn get_insertion_sort(inner: &Inner) -> Vec<(String, &Data)> {
-    let mut acc = vec![];
-    for (word, data) in &inner.store {
-        acc.push((word.to_owned(), data));
+    /// Content of `palabras.on`.
+    pub fn palabras(&self) -> String {
+        self.order.join("\n")
     }
-    acc
 }
 
-pub fn init() -> HashMap<String, String> {
-    let mut store = HashMap::new();
-    let content = get_public_domain_books();
-    let inner = parse_content(content);
-
-    let acc = match "word" {
-        "insertion" => get_insertion_sort(&inner),
-        "word" => get_word_file(&inner),
-        _ => get_popularity_sort(&inner),
-    };
-    for (word, data) in &acc {
-        let x = data
-            .sentences
-            .iter()
-            .take(TAKE)
-            .fold(String::new(), |mut acc, b| {
-                acc.push_str(&stitch_words_sentences(*b, &inner));
-                acc
-            });
+/// Examples of the words of `list` found in the corpus.
+pub fn examples_of(list: &[String]) -> ExampleStore {
+    let inner = corpus();
+    let acc = list
+        .iter()
+        .filter_map(|word| Word::get(word).and_then(|w| inner.store.get_key_value(&w)))
+        .map(|(word, data)| (*word, data))
+        .collect();
+    ExampleStore::new(inner, acc)
+}
 
-        let sentence = format!("{}\n", x.trim());
-        store.insert(word.clone(), sentence);
-    }
-    store
+/// Examples of every valid word of the corpus.
+pub fn init_get_system() -> ExampleStore {
+    let inner = corpus();
+    let acc = inner
+        .store
+        .iter()
+        .filter(|(word, _)| English::valid_english(word) && word.len() > 1)
+        .map(|(word, data)| (*word, data))
+        .collect();
+    ExampleStore::new(inner, acc)
+}
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Frequency {
+    pub count: usize,
+    pub books: usize,
 }
 
-fn get_system_sort(inner: &Inner) -> Vec<(String, &Data)> {
-    let mut acc = vec![];
-    for (word, data) in &inner.store {
-        if English::valid_english(word) && word.len() > 1 {
-            acc.push((word.to_owned(), data));
-        }
-    }
+/// Truecasing statistics of the raw corpus tokens.
+pub fn init_casing() -> Casing {
+    Casing::new(corpus().list.iter().map(|token| token.as_str()))
+}
 
-    acc.sort_by(|(_, a), (_, b)| a.sentences.len().cmp(&b.sentences.len()).reverse());
-    let todo: Vec<_> = acc.clone().iter().map(|(w, _)| w.to_owned()).collect();
-    fs::write("palabras.on", todo.join("\n")).unwrap();
-    acc
+/// Counts every word of the corpus together with the number of books it
+/// shows up in.
+pub fn init_frequency() -> HashMap<String, Frequency> {
+    frequency(corpus())
 }
 
-pub fn init_get_system() -> HashMap<String, (usize, String)> {
+fn frequency(corpus: &Corpus) -> HashMap<String, Frequency> {
     let mut store = HashMap::new();
-    let content = get_public_domain_books();
-    let inner = parse_content(content);
-
-    let acc = get_system_sort(&inner);
-    for (word, data) in &acc {
-        let x = data
-            .sentences
-            .iter()
-            .take(TAKE)
-            .fold(String::new(), |mut acc, b| {
-                acc.push_str(&stitch_words_sentences(*b, &inner));
-                acc
-            });
-
-        let sentence = format!("{}\n", x.trim());
-        store.insert(word.clone(), (data.sentences.len(), sentence));
+    for (word, data) in &corpus.store {
+        store.insert(word.to_string(), corpus.count(data));
     }
     store
 }
-pub fn write_to_file_system(
-    file_name: &Path,
-    list: &Vec<String>,
-    store: &HashMap<String, (usize, String)>,
-) {
+
+/// Splits the sentences stored for a word by `init` or `init_get_system`.
+pub fn examples(sentences: &str) -> Vec<String> {
+    sentences
+        .split('\n')
+        .map(|sentence| sentence.trim())
+        .filter(|sentence| !sentence.is_empty())
+        .map(|sentence| sentence.to_owned())
+        .collect()
+}
+
+/// Numbered listing of the words of `list` that have examples in `store`.
+pub fn compose(list: &[String], store: &HashMap<String, (usize, String)>) -> Option<String> {
     let mut acc = vec![];
 
     for (index, w) in list.iter().enumerate() {
-        if let Some((_rank, s)) = store.get(w) {
+        if let Some((_count, s)) = store.get(w) {
             let content = format!(
                 "
                         {}: {}\n{}",
@@ -299,26 +386,72 @@ pub fn write_to_file_system(
             acc.push(content);
         }
     }
-    if acc.len() > 0 {
-        fs::write(file_name, acc.join("\n")).unwrap();
+    if acc.is_empty() {
+        return None;
     }
+    Some(acc.join("\n"))
 }
-pub fn write_to_file(file_name: &Path, list: &Vec<String>, store: &HashMap<String, String>) {
-    let mut acc = vec![];
 
-    for (index, w) in list.iter().enumerate() {
-        if let Some(s) = store.get(w) {
-            let content = format!(
-                "
-                        {}: {}\n{}",
-                index + 1,
-                w,
-                s
-            );
-            acc.push(content);
-        }
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn examples_test() {
+        let list = examples("la, casa grande \nmi, casa roja \n");
+        assert_eq!(list, vec!["la, casa grande", "mi, casa roja"]);
+        assert!(examples("\n").is_empty());
+    }
+    fn books() -> Vec<String> {
+        vec![
+            "Casa, casa perro.".to_owned(),
+            "casa gato".to_owned(),
+            "".to_owned(),
+            "el gato   y la casa".to_owned(),
+        ]
+    }
+    #[test]
+    fn index_test() {
+        let corpus = index(&books());
+        assert_eq!(corpus, index_sequential(&books()));
+        assert_eq!(corpus.tokens(), 10);
+        assert_eq!(corpus.books, vec![0, 3, 5, 5]);
+        assert_eq!(corpus.store[&Word::new("casa")].sentences, vec![0, 1, 3, 9]);
+        assert_eq!(*corpus.store.get_index(2).unwrap().0, "gato");
+    }
+    #[test]
+    fn concordance_test() {
+        let corpus = index(&books());
+        let list = corpus.concordance("casa", 2, 3);
+        let lines: Vec<_> = list
+            .iter()
+            .map(|n| (n.left.as_str(), n.word.as_str(), n.right.as_str(), n.book))
+            .collect();
+        assert_eq!(
+            lines,
+            vec![
+                ("", "Casa,", "casa perro.", 0),
+                ("Casa,", "casa", "perro.", 0),
+                ("", "casa", "gato", 1),
+            ]
+        );
+        assert!(corpus.concordance("nada", 2, 3).is_empty());
+        assert_eq!(
+            corpus.examples_of("gato"),
+            vec![
+                "Casa, casa perro. casa, gato el gato y la casa",
+                "perro. casa gato el, gato y la casa"
+            ]
+        );
+        assert_eq!(
+            corpus.frequency_of("gato"),
+            Some(Frequency { count: 2, books: 2 })
+        );
     }
-    if acc.len() > 0 {
-        fs::write(file_name, acc.join("\n")).unwrap();
+    #[test]
+    fn frequency_test() {
+        let store = frequency(&index(&books()));
+        assert_eq!(store["casa"], Frequency { count: 4, books: 3 });
+        assert_eq!(store["perro"], Frequency { count: 1, books: 1 });
+        assert_eq!(store["gato"], Frequency { count: 2, books: 2 });
     }
 }
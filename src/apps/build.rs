@@ -0,0 +1,336 @@
+use serde::Serialize;
+use std::{collections::HashMap, str::FromStr};
+
+use crate::apps::{
+    anki::{self, DeckBy},
+    booktore, cloze,
+    level::{self, Level},
+    output::BuildOutput,
+    Formats, Vocabulary,
+};
+
+/// Columns of the `ranked` and `json` formats.
+const COLUMNS: [&str; 5] = ["rank", "word", "folder", "frequency", "level"];
+
+/// Shapes a tipo can be built in, chosen per tipo in `config/formats.on`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BuildFormat {
+    /// `<tipo>.on`, one word per line in vocabulary order.
+    Plain,
+    /// `<tipo>.csv` with a header, most frequent words first.
+    Ranked,
+    /// `<tipo>.json`, the rows of `ranked` as objects.
+    Json,
+    /// `<tipo>/<folder>.on`, one plain list per word length.
+    Length,
+}
+
+impl BuildFormat {
+    pub const ALL: [BuildFormat; 4] = [
+        BuildFormat::Plain,
+        BuildFormat::Ranked,
+        BuildFormat::Json,
+        BuildFormat::Length,
+    ];
+
+    pub fn name(&self) -> &str {
+        match self {
+            BuildFormat::Plain => "plain",
+            BuildFormat::Ranked => "ranked",
+            BuildFormat::Json => "json",
+            BuildFormat::Length => "length",
+        }
+    }
+
+    fn columns(&self) -> Vec<&'static str> {
+        match self {
+            BuildFormat::Plain | BuildFormat::Length => vec!["word"],
+            BuildFormat::Ranked | BuildFormat::Json => COLUMNS.to_vec(),
+        }
+    }
+
+    fn description(&self) -> &'static str {
+        match self {
+            BuildFormat::Plain => "one word per line in vocabulary order",
+            BuildFormat::Ranked => "CSV with a header row, most frequent words first",
+            BuildFormat::Json => "array of objects, most frequent words first",
+            BuildFormat::Length => "one word per line, one file per word length",
+        }
+    }
+
+    /// Files of one tipo, `rows` are in vocabulary order.
+    fn render<'a>(&self, tipo: &str, rows: &'a [Row]) -> Vec<(String, Vec<&'a Row>)> {
+        let ranked = || {
+            let mut list: Vec<&Row> = rows.iter().collect();
+            list.sort_by_key(|row| row.rank.unwrap_or(usize::MAX));
+            list
+        };
+        match self {
+            BuildFormat::Plain => vec![(format!("{}.on", tipo), rows.iter().collect())],
+            BuildFormat::Ranked => vec![(format!("{}.csv", tipo), ranked())],
+            BuildFormat::Json => vec![(format!("{}.json", tipo), ranked())],
+            BuildFormat::Length => {
+                let mut folders: Vec<(&str, Vec<&Row>)> = vec![];
+                for row in rows {
+                    match folders.iter_mut().find(|(folder, _)| *folder == row.folder) {
+                        Some((_, list)) => list.push(row),
+                        None => folders.push((&row.folder, vec![row])),
+                    }
+                }
+                folders.sort_by_key(|(folder, _)| (folder.parse::<usize>().ok(), *folder));
+                folders
+                    .into_iter()
+                    .map(|(folder, list)| (format!("{}/{}.on", tipo, folder), list))
+                    .collect()
+            }
+        }
+    }
+
+    fn content(&self, list: &[&Row]) -> String {
+        match self {
+            BuildFormat::Plain | BuildFormat::Length => {
+                list.iter().fold(String::new(), |mut acc, row| {
+                    acc.push_str(&format!("{}\n", row.word));
+                    acc
+                })
+            }
+            BuildFormat::Ranked => {
+                let mut writer = csv::Writer::from_writer(vec![]);
+                writer.write_record(COLUMNS).unwrap();
+                for row in list {
+                    writer.write_record(row.csv()).unwrap();
+                }
+                String::from_utf8(writer.into_inner().unwrap()).unwrap()
+            }
+            BuildFormat::Json => format!("{}\n", serde_json::to_string_pretty(list).unwrap()),
+        }
+    }
+}
+
+impl FromStr for BuildFormat {
+    type Err = String;
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        match BuildFormat::ALL
+            .iter()
+            .find(|format| format.name() == input)
+        {
+            Some(format) => Ok(*format),
+            None => Err(format!(
+                "({}) is not a build format, use plain, ranked, json or length",
+                input
+            )),
+        }
+    }
+}
+
+/// One word of the build. `rank` is its corpus rank, 1 being the most
+/// frequent word.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+struct Row {
+    rank: Option<usize>,
+    word: String,
+    folder: String,
+    frequency: Option<usize>,
+    level: Option<Level>,
+}
+
+impl Row {
+    fn csv(&self) -> Vec<String> {
+        let number = |n: Option<usize>| n.map_or(String::new(), |n| n.to_string());
+        vec![
+            number(self.rank),
+            self.word.to_owned(),
+            self.folder.to_owned(),
+            number(self.frequency),
+            self.level.map_or(String::new(), |n| n.to_string()),
+        ]
+    }
+}
+
+/// One file of the build as listed in `manifest.json`.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+struct Schema<'a> {
+    path: String,
+    tipo: &'a str,
+    format: BuildFormat,
+    words: usize,
+    columns: Vec<&'a str>,
+    description: &'a str,
+}
+
+/// Every tipo in the formats `formats` gives it, limited to the words of
+/// `max_level` or easier, and a `manifest.json` describing each file.
+/// Ranks come from the booktore corpus `store` when given and from the
+/// vocabulary levels otherwise.
+pub fn build(
+    vocabulary: &Vocabulary,
+    formats: &Formats,
+    store: Option<&HashMap<String, (usize, String)>>,
+    max_level: Option<Level>,
+) -> BuildOutput {
+    let mut output = BuildOutput::new();
+    let ranks = store.map(level::ranks);
+    let mut data: Vec<_> = vocabulary.data().collect();
+    data.sort_by_key(|(tipo, _)| *tipo);
+    let mut manifest = vec![];
+    for (tipo, list) in data {
+        let rows: Vec<Row> = list
+            .iter()
+            .filter(|mas| max_level.is_none_or(|max| mas.level.is_some_and(|l| l <= max)))
+            .map(|mas| {
+                let word = mas.word.as_str();
+                Row {
+                    rank: ranks.as_ref().map_or(mas.rank, |n| n.get(word).copied()),
+                    word: word.to_owned(),
+                    folder: mas.folder.to_owned(),
+                    frequency: store.and_then(|n| n.get(word)).map(|(count, _)| *count),
+                    level: mas.level,
+                }
+            })
+            .collect();
+        if rows.is_empty() {
+            continue;
+        }
+        for format in formats.of(tipo) {
+            for (path, list) in format.render(tipo, &rows) {
+                output.push(&path, format.content(&list));
+                manifest.push(Schema {
+                    path,
+                    tipo,
+                    format,
+                    words: list.len(),
+                    columns: format.columns(),
+                    description: format.description(),
+                });
+            }
+        }
+    }
+    if !manifest.is_empty() {
+        let content = serde_json::to_string_pretty(&manifest).unwrap();
+        output.push("manifest.json", format!("{}\n", content));
+    }
+    output
+}
+
+/// `<tipo>.off` listings of every vocabulary word with its corpus examples,
+/// most frequent first.
+pub fn booktore(vocabulary: &Vocabulary, store: &HashMap<String, (usize, String)>) -> BuildOutput {
+    let mut output = BuildOutput::new();
+    let mut data: Vec<_> = vocabulary.data().collect();
+    data.sort_by_key(|(tipo, _)| *tipo);
+    for (tipo, data) in data {
+        let mut list: Vec<_> = data.iter().map(|n| n.word.to_string()).collect();
+        list.sort_by(|a, b| {
+            let value_a = store.get(a).map_or(0, |x| x.0);
+            let value_b = store.get(b).map_or(0, |x| x.0);
+            value_b.cmp(&value_a) // Reversed order
+        });
+
+        if let Some(content) = booktore::compose(&list, store) {
+            output.push(format!("{}.off", tipo), content);
+        }
+    }
+    output
+}
+
+/// One `<deck>.tsv` Anki deck per tipo or length.
+pub fn anki(
+    vocabulary: &Vocabulary,
+    store: &HashMap<String, (usize, String)>,
+    deck_by: DeckBy,
+) -> BuildOutput {
+    let mut output = BuildOutput::new();
+    for (deck, cards) in anki::decks(vocabulary, store, deck_by) {
+        output.push(format!("{}.tsv", deck), anki::tsv(&deck, &cards));
+    }
+    output
+}
+
+/// `<tipo>.json` and printable `<tipo>.txt` cloze exercises.
+pub fn cloze(exercises: &[cloze::Exercise]) -> BuildOutput {
+    let mut output = BuildOutput::new();
+    let mut tipos: HashMap<String, Vec<cloze::Exercise>> = HashMap::new();
+    for exercise in exercises {
+        tipos
+            .entry(exercise.tipo.to_owned())
+            .or_default()
+            .push(exercise.to_owned());
+    }
+    let mut tipos: Vec<_> = tipos.into_iter().collect();
+    tipos.sort_by(|(a, _), (b, _)| a.cmp(b));
+    for (tipo, list) in tipos {
+        output.push(format!("{}.json", tipo), cloze::json(&list));
+        output.push(format!("{}.txt", tipo), cloze::text(&list));
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::apps::{intern::Word, token, Mas};
+    fn vocabulary() -> Vocabulary {
+        let mut vocabulary = Vocabulary::new();
+        for (word, tipo, level) in [
+            ("casa", "esencial", Some(Level::A1)),
+            ("perro", "esencial", Some(Level::B2)),
+            ("gato", "ANY", None),
+        ] {
+            let mut mas = Mas::new();
+            mas.word = Word::new(word);
+            mas.tipo = tipo.to_owned();
+            mas.folder = token::folder(word);
+            mas.level = level;
+            vocabulary.push(mas);
+        }
+        vocabulary
+    }
+    #[test]
+    fn build_test() {
+        let formats = Formats::new();
+        let output = build(&vocabulary(), &formats, None, None);
+        assert_eq!(output.get("esencial.on"), Some("casa\nperro\n"));
+        assert_eq!(output.get("ANY.on"), Some("gato\n"));
+        let output = build(&vocabulary(), &formats, None, Some(Level::B1));
+        assert_eq!(output.get("esencial.on"), Some("casa\n"));
+        assert_eq!(output.get("ANY.on"), None);
+    }
+    #[test]
+    fn formats_test() {
+        let formats = Formats::parse("// per tipo\nesencial: ranked json\n*: length\nANY: nope\n");
+        assert_eq!(formats.of("ANY"), vec![BuildFormat::Length]);
+        let mut store = HashMap::new();
+        store.insert("perro".to_owned(), (5, String::new()));
+        store.insert("casa".to_owned(), (2, String::new()));
+        store.insert("el".to_owned(), (9, String::new()));
+        let output = build(&vocabulary(), &formats, Some(&store), None);
+        assert_eq!(output.get("esencial.on"), None);
+        assert_eq!(
+            output.get("esencial.csv"),
+            Some("rank,word,folder,frequency,level\n2,perro,5,5,B2\n3,casa,4,2,A1\n")
+        );
+        let json: serde_json::Value =
+            serde_json::from_str(output.get("esencial.json").unwrap()).unwrap();
+        assert_eq!(json[0]["word"], "perro");
+        assert_eq!(json[1]["level"], "A1");
+        assert_eq!(output.get("ANY/4.on"), Some("gato\n"));
+        let manifest: serde_json::Value =
+            serde_json::from_str(output.get("manifest.json").unwrap()).unwrap();
+        assert_eq!(manifest[0]["path"], "ANY/4.on");
+        assert_eq!(manifest[1]["format"], "ranked");
+        assert_eq!(manifest[1]["words"], 2);
+        assert_eq!(manifest[2]["columns"][0], "rank");
+        assert!("csv".parse::<BuildFormat>().is_err());
+    }
+    #[test]
+    fn booktore_test() {
+        let mut store = HashMap::new();
+        store.insert("perro".to_owned(), (5, "el, perro ladra \n".to_owned()));
+        store.insert("casa".to_owned(), (2, "la, casa roja \n".to_owned()));
+        let output = booktore(&vocabulary(), &store);
+        let content = output.get("esencial.off").unwrap();
+        assert!(content.find("1: perro").unwrap() < content.find("2: casa").unwrap());
+        assert_eq!(output.get("ANY.off"), None);
+    }
+}
@@ -0,0 +1,136 @@
+use std::collections::HashMap;
+
+use crate::apps::normal;
+
+/// Least mid-sentence occurrences needed to trust the counts of a word.
+const MIN_COUNT: usize = 3;
+/// Share of mid-sentence occurrences that must be capitalized for a word to
+/// be taken as a proper noun.
+const MIN_RATIO: f64 = 0.9;
+
+#[derive(Debug, Default, Clone, PartialEq)]
+struct Count {
+    upper: usize,
+    lower: usize,
+    /// Capitalized spellings, "España" or "USA", and how often they appear.
+    forms: HashMap<String, usize>,
+}
+
+/// Truecasing from corpus statistics. Words at the start of a sentence are
+/// capitalized anyway, so only the other occurrences are counted: a word is
+/// a proper noun when it is nearly always capitalized there.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Casing {
+    store: HashMap<String, Count>,
+    /// Tipo proper nouns are filed under, `None` keeps the requested one.
+    proper: Option<String>,
+}
+
+fn is_sentence_end(token: &str) -> bool {
+    token
+        .trim_end_matches(['"', '\'', ')', '»', '”'])
+        .ends_with(['.', '!', '?', ':'])
+}
+
+impl Casing {
+    /// Counts raw tokens in reading order, punctuation included.
+    pub fn new<'a, T: IntoIterator<Item = &'a str>>(tokens: T) -> Self {
+        let mut casing = Casing::default();
+        let mut start = true;
+        for token in tokens {
+            let is_start = start || token.starts_with(['¿', '¡']);
+            start = is_sentence_end(token);
+            if is_start {
+                continue;
+            }
+            let word = normal::nfc(token.trim_matches(|letter: char| !letter.is_alphabetic()));
+            if word.is_empty() || !word.chars().all(|letter| letter.is_alphabetic()) {
+                continue;
+            }
+            let count = casing.store.entry(word.to_lowercase()).or_default();
+            if word.starts_with(char::is_uppercase) {
+                count.upper += 1;
+                *count.forms.entry(word).or_default() += 1;
+            } else {
+                count.lower += 1;
+            }
+        }
+        casing
+    }
+
+    /// Files proper nouns under `tipo`.
+    pub fn proper(mut self, tipo: Option<&str>) -> Self {
+        self.proper = tipo.map(|tipo| tipo.to_owned());
+        self
+    }
+
+    pub fn is_proper(&self, word: &str) -> bool {
+        self.store
+            .get(&word.to_lowercase())
+            .filter(|count| count.upper + count.lower >= MIN_COUNT)
+            .is_some_and(|count| {
+                count.upper as f64 / (count.upper + count.lower) as f64 >= MIN_RATIO
+            })
+    }
+
+    /// The most common capitalized spelling of a proper noun, the lowercase
+    /// word otherwise.
+    pub fn canonical(&self, word: &str) -> String {
+        let lower = word.to_lowercase();
+        if !self.is_proper(&lower) {
+            return lower;
+        }
+        self.store[&lower]
+            .forms
+            .iter()
+            .max_by(|(a, x), (b, y)| x.cmp(y).then(b.cmp(a)))
+            .map_or(lower.to_owned(), |(form, _)| form.to_owned())
+    }
+
+    /// Canonical spelling of `word` and the tipo it is filed under.
+    pub fn route(&self, word: &str, tipo: &str) -> (String, String) {
+        let canonical = self.canonical(word);
+        match &self.proper {
+            Some(proper) if self.is_proper(word) => (canonical, proper.to_owned()),
+            _ => (canonical, tipo.to_owned()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    fn casing() -> Casing {
+        let text = "España es grande. Vivo en España y en españa, no. \
+                    Dios sabe. Dijo que Dios existe y que Dios ayuda. Gracias a Dios. \
+                    La casa es mía. Tengo una casa, esta casa y otra casa. \
+                    ¿Casa? Casa.";
+        Casing::new(text.split_whitespace())
+    }
+    #[test]
+    fn proper_test() {
+        let casing = casing();
+        assert!(casing.is_proper("dios"));
+        assert!(!casing.is_proper("casa"));
+        assert!(!casing.is_proper("españa"));
+        assert!(!casing.is_proper("musk"));
+    }
+    #[test]
+    fn route_test() {
+        let casing = casing().proper(Some("nombres"));
+        assert_eq!(casing.canonical("DIOS"), "Dios");
+        assert_eq!(casing.canonical("Casa"), "casa");
+        assert_eq!(
+            casing.route("dios", "ANY"),
+            ("Dios".to_owned(), "nombres".to_owned())
+        );
+        assert_eq!(
+            casing.route("casa", "ANY"),
+            ("casa".to_owned(), "ANY".to_owned())
+        );
+        assert_eq!(
+            Casing::default().route("dios", "ANY"),
+            ("dios".to_owned(), "ANY".to_owned())
+        );
+    }
+}
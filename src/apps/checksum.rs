@@ -0,0 +1,25 @@
+const OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+const PRIME: u64 = 0x0100_0000_01b3;
+
+/// 64 bit FNV-1a, stable across platforms and compiler versions unlike
+/// `std::collections::hash_map::DefaultHasher`.
+pub fn fnv(input: &[u8]) -> u64 {
+    input.iter().fold(OFFSET, |hash, byte| {
+        (hash ^ *byte as u64).wrapping_mul(PRIME)
+    })
+}
+
+pub fn hex(input: &[u8]) -> String {
+    format!("{:016x}", fnv(input))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn fnv_test() {
+        assert_eq!(fnv(b""), OFFSET);
+        assert_eq!(hex(b"a"), "af63dc4c8601ec8c");
+        assert_ne!(hex("canción".as_bytes()), hex(b"cancion"));
+    }
+}
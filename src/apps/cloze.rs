@@ -0,0 +1,192 @@
+use serde::Serialize;
+use std::collections::HashMap;
+
+use crate::apps::{booktore, checksum, normal, Mas, Vocabulary};
+
+/// Wrong options offered next to the answer.
+const DISTRACTORS: usize = 3;
+const BLANK: &str = "_____";
+
+/// One fill-in-the-blank exercise. `options` are sorted, the answer is
+/// `options[answer]`.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Exercise {
+    pub word: String,
+    pub tipo: String,
+    pub folder: String,
+    pub sentence: String,
+    pub options: Vec<String>,
+    pub answer: usize,
+}
+
+/// `sentence` with the tokens that are `word` blanked, ignoring case and
+/// keeping punctuation. `None` when the word is not in it.
+pub fn blank(sentence: &str, word: &str) -> Option<String> {
+    let word = normal::word(word);
+    let mut found = false;
+    let list: Vec<String> = sentence
+        .split_whitespace()
+        .map(|token| {
+            let core = token.trim_matches(|letter: char| !letter.is_alphabetic());
+            if core.is_empty() || normal::word(core) != word {
+                return token.to_owned();
+            }
+            found = true;
+            let start = token.find(core).unwrap();
+            format!(
+                "{}{}{}",
+                &token[..start],
+                BLANK,
+                &token[start + core.len()..]
+            )
+        })
+        .collect();
+    found.then(|| list.join(" "))
+}
+
+/// Words of the same tipo as `mas` to tell apart from it, those of the same
+/// length first, then words of the same length from other tipos. The choice
+/// is stable for a given word.
+pub fn distractors(vocabulary: &Vocabulary, mas: &Mas) -> Vec<String> {
+    let mut list: Vec<&Mas> = vocabulary
+        .words()
+        .iter()
+        .filter(|other| other.word != mas.word)
+        .filter(|other| other.tipo == mas.tipo || other.folder == mas.folder)
+        .collect();
+    list.sort_by_key(|other| {
+        let key = format!("{}:{}", mas.word, other.word);
+        (
+            other.tipo != mas.tipo,
+            other.folder != mas.folder,
+            checksum::fnv(key.as_bytes()),
+        )
+    });
+    let mut acc: Vec<String> = vec![];
+    for other in list {
+        if acc.len() == DISTRACTORS {
+            break;
+        }
+        if !acc.iter().any(|word| *word == other.word.as_str()) {
+            acc.push(other.word.to_string());
+        }
+    }
+    acc
+}
+
+/// An exercise for every vocabulary word with a corpus sentence that
+/// contains it and `DISTRACTORS` words to mix it with. `store` is the
+/// booktore corpus, see `init_get_system`.
+pub fn exercises(
+    vocabulary: &Vocabulary,
+    store: &HashMap<String, (usize, String)>,
+) -> Vec<Exercise> {
+    let mut acc = vec![];
+    for mas in vocabulary.words() {
+        let sentences = match store.get(mas.word.as_str()) {
+            Some((_, sentences)) => booktore::examples(sentences),
+            None => continue,
+        };
+        let sentence = match sentences.iter().find_map(|n| blank(n, &mas.word)) {
+            Some(sentence) => sentence,
+            None => continue,
+        };
+        let mut options = distractors(vocabulary, mas);
+        if options.len() < DISTRACTORS {
+            continue;
+        }
+        options.push(mas.word.to_string());
+        options.sort();
+        let answer = options.iter().position(|n| n == mas.word.as_str()).unwrap();
+        acc.push(Exercise {
+            word: mas.word.to_string(),
+            tipo: mas.tipo.to_owned(),
+            folder: mas.folder.to_owned(),
+            sentence,
+            options,
+            answer,
+        });
+    }
+    acc
+}
+
+pub fn json(list: &[Exercise]) -> String {
+    format!("{}\n", serde_json::to_string_pretty(list).unwrap())
+}
+
+/// Printable sheet, the answers are listed at the end.
+pub fn text(list: &[Exercise]) -> String {
+    let letter = |index: usize| (b'a' + index as u8) as char;
+    let mut acc = String::new();
+    for (index, exercise) in list.iter().enumerate() {
+        acc.push_str(&format!("{}. {}\n", index + 1, exercise.sentence));
+        let options: Vec<String> = exercise
+            .options
+            .iter()
+            .enumerate()
+            .map(|(index, option)| format!("{}) {}", letter(index), option))
+            .collect();
+        acc.push_str(&format!("   {}\n\n", options.join("   ")));
+    }
+    acc.push_str("Respuestas:\n");
+    for (index, exercise) in list.iter().enumerate() {
+        acc.push_str(&format!(
+            "{}. {}) {}\n",
+            index + 1,
+            letter(exercise.answer),
+            exercise.word
+        ));
+    }
+    acc
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::apps::fixture::Fixture;
+    #[test]
+    fn blank_test() {
+        assert_eq!(
+            blank("la, Casa. grande casa", "casa"),
+            Some("la, _____. grande _____".to_owned())
+        );
+        assert_eq!(blank("mi casona", "casa"), None);
+        assert_eq!(blank("¿Está?", "está"), Some("¿_____?".to_owned()));
+    }
+    #[test]
+    fn exercises_test() {
+        let vocabulary = Fixture::new()
+            .types(&["ANY", "esencial", "raro"])
+            .words("ANY", &["casa", "gato", "loro", "perro", "mesa"])
+            .words("esencial", &["pato"])
+            .words("raro", &["murciélago"])
+            .vocabulary();
+        let store: HashMap<_, _> = [
+            (
+                "casa".to_owned(),
+                (2, "mi, gato \nla, casa roja \n".to_owned()),
+            ),
+            ("pato".to_owned(), (1, "un, pato \n".to_owned())),
+            ("murciélago".to_owned(), (1, "un murciélago \n".to_owned())),
+        ]
+        .into();
+        let list = exercises(&vocabulary, &store);
+        assert_eq!(list.len(), 2);
+        let casa = &list[0];
+        assert_eq!(casa.sentence, "la, _____ roja");
+        assert_eq!(casa.options.len(), DISTRACTORS + 1);
+        assert_eq!(casa.options[casa.answer], "casa");
+        assert!(!casa.options.contains(&"perro".to_owned()));
+        let pato = &list[1];
+        assert_eq!(pato.options.len(), DISTRACTORS + 1);
+        assert_eq!(pato.options[pato.answer], "pato");
+        assert!(pato.options.iter().all(|n| n.chars().count() == 4));
+        assert_eq!(distractors(&vocabulary, &vocabulary.words()[0]).len(), 3);
+
+        let text = text(&list);
+        assert!(text.starts_with("1. la, _____ roja\n   a) "));
+        assert!(text.ends_with("2. d) pato\n"));
+        let json: serde_json::Value = serde_json::from_str(&json(&list)).unwrap();
+        assert_eq!(json[1]["sentence"], "un, _____");
+    }
+}
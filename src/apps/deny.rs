@@ -0,0 +1,332 @@
+use std::{
+    collections::{BTreeMap, HashSet},
+    fs,
+    path::{Path, PathBuf},
+};
+
+use crate::apps::{journal, normal, off::OffFile};
+
+const COMMENT: &str = "//";
+
+/// One denied word. `comments` are the `//` lines right above it, such as
+/// section titles, and move together with the word.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Entry {
+    pub word: String,
+    pub note: Option<String>,
+    pub comments: Vec<String>,
+}
+
+impl Entry {
+    fn parse(line: &str) -> Self {
+        match line.split_once(COMMENT) {
+            Some((word, note)) => Self {
+                word: word.trim().to_owned(),
+                note: Some(note.trim().to_owned()).filter(|n| !n.is_empty()),
+                comments: vec![],
+            },
+            None => Self {
+                word: line.trim().to_owned(),
+                note: None,
+                comments: vec![],
+            },
+        }
+    }
+
+    fn line(&self) -> String {
+        match &self.note {
+            Some(note) => format!("{} {} {}", self.word, COMMENT, note),
+            None => self.word.to_owned(),
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct Diff {
+    pub left: Vec<String>,
+    pub right: Vec<String>,
+    pub both: Vec<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct DenyList {
+    pub path: PathBuf,
+    pub comments: Vec<String>,
+    pub entries: Vec<Entry>,
+    /// Comments after the last word.
+    pub trailing: Vec<String>,
+}
+
+impl DenyList {
+    pub fn new<T: AsRef<Path>>(path: T) -> Self {
+        Self {
+            path: path.as_ref().to_path_buf(),
+            comments: Vec::new(),
+            entries: Vec::new(),
+            trailing: Vec::new(),
+        }
+    }
+
+    pub fn read<T: AsRef<Path>>(path: T) -> Self {
+        let content = fs::read_to_string(&path).unwrap_or_default();
+        DenyList::new(path).parse(&content)
+    }
+
+    fn parse(mut self, content: &str) -> Self {
+        let list: Vec<_> = content
+            .split('\n')
+            .map(|line| line.trim())
+            .filter(|line| !line.is_empty())
+            .collect();
+        self.comments = list
+            .iter()
+            .take_while(|line| line.starts_with(COMMENT))
+            .map(|line| line.to_string())
+            .collect();
+        let mut pending = vec![];
+        for line in list.iter().skip(self.comments.len()) {
+            if line.starts_with(COMMENT) {
+                pending.push(line.to_string());
+                continue;
+            }
+            let mut entry = Entry::parse(line);
+            if entry.word.is_empty() {
+                continue;
+            }
+            entry.comments = std::mem::take(&mut pending);
+            self.entries.push(entry);
+        }
+        self.trailing = pending;
+        self
+    }
+
+    pub fn words(&self) -> Vec<&str> {
+        self.entries
+            .iter()
+            .map(|entry| entry.word.as_str())
+            .collect()
+    }
+
+    /// Lowercases and NFC-normalizes every word, then sorts and removes
+    /// duplicates. When a word is repeated the notes of every copy are kept.
+    pub fn normalize(mut self) -> Self {
+        let mut store: BTreeMap<String, (Vec<String>, Vec<String>)> = BTreeMap::new();
+        for entry in self.entries {
+            let (notes, comments) = store.entry(normal::word(&entry.word)).or_default();
+            if let Some(note) = entry.note {
+                if !notes.contains(&note) {
+                    notes.push(note);
+                }
+            }
+            for comment in entry.comments {
+                if !comments.contains(&comment) {
+                    comments.push(comment);
+                }
+            }
+        }
+        self.entries = store
+            .into_iter()
+            .map(|(word, (notes, comments))| Entry {
+                word,
+                note: Some(notes.join("; ")).filter(|n| !n.is_empty()),
+                comments,
+            })
+            .collect();
+        self
+    }
+
+    pub fn diff(&self, other: &DenyList) -> Diff {
+        let left: HashSet<String> = self.words().iter().map(|w| normal::word(w)).collect();
+        let right: HashSet<String> = other.words().iter().map(|w| normal::word(w)).collect();
+        let mut diff = Diff {
+            left: left.difference(&right).cloned().collect(),
+            right: right.difference(&left).cloned().collect(),
+            both: left.intersection(&right).cloned().collect(),
+        };
+        diff.left.sort();
+        diff.right.sort();
+        diff.both.sort();
+        diff
+    }
+
+    pub fn merge(mut self, other: DenyList) -> Self {
+        for comment in other.comments {
+            if !self.comments.contains(&comment) {
+                self.comments.push(comment);
+            }
+        }
+        self.entries.extend(other.entries);
+        for comment in other.trailing {
+            if !self.trailing.contains(&comment) {
+                self.trailing.push(comment);
+            }
+        }
+        self.normalize()
+    }
+
+    /// Words of the list that are already part of the curated vocabulary.
+    pub fn collisions(&self, vocabulary: &[String]) -> Vec<String> {
+        let store: HashSet<String> = vocabulary.iter().map(|w| normal::word(w)).collect();
+        let mut acc: Vec<String> = self
+            .words()
+            .iter()
+            .map(|w| normal::word(w))
+            .filter(|w| store.contains(w))
+            .collect();
+        acc.sort();
+        acc.dedup();
+        acc
+    }
+
+    /// Adds `words` to the list, recording `source` as the provenance of
+    /// each one. Returns how many of them were not denied before.
+    pub fn promote(&mut self, words: &[String], source: &str) -> usize {
+        let mut store: HashSet<String> = self.words().iter().map(|w| normal::word(w)).collect();
+        let mut added = 0;
+        for word in words {
+            let word = normal::word(word);
+            if word.is_empty() || !store.insert(word.to_owned()) {
+                continue;
+            }
+            self.entries.push(Entry {
+                word,
+                note: Some(format!("from {}", source)),
+                comments: vec![],
+            });
+            added += 1;
+        }
+        added
+    }
+
+    pub fn content(&self) -> String {
+        let mut list = self.comments.clone();
+        for entry in &self.entries {
+            list.extend(entry.comments.iter().cloned());
+            list.push(entry.line());
+        }
+        list.extend(self.trailing.iter().cloned());
+        format!("{}\n", list.join("\n"))
+    }
+
+    pub fn write(&self) {
+        fs::write(&self.path, self.content()).unwrap();
+    }
+}
+
+fn print_list(title: &str, list: &[String]) {
+    println!("{}: {}", title, list.len());
+    for word in list {
+        println!("  {}", word);
+    }
+}
+
+pub fn run(args: &[String], vocabulary: impl FnOnce() -> Vec<String>) {
+    let arg = |index: usize| args.get(index).map(|a| a.as_str());
+    match (arg(0), arg(1), arg(2)) {
+        (Some("normalize"), Some(path), None) => {
+            let before = DenyList::read(path);
+            let total = before.entries.len();
+            let list = before.normalize();
+            journal::journal().record("deny normalize", &[path], || list.write());
+            println!("{}: {} -> {} words", path, total, list.entries.len());
+        }
+        (Some("diff"), Some(left), Some(right)) => {
+            let diff = DenyList::read(left).diff(&DenyList::read(right));
+            print_list(&format!("only in {}", left), &diff.left);
+            print_list(&format!("only in {}", right), &diff.right);
+            println!("in both: {}", diff.both.len());
+        }
+        (Some("merge"), Some(into), Some(from)) => {
+            let list = DenyList::read(into).merge(DenyList::read(from));
+            journal::journal().record("deny merge", &[into], || list.write());
+            println!("{}: {} words", into, list.entries.len());
+        }
+        (Some("collisions"), Some(path), None) => {
+            let list = DenyList::read(path);
+            print_list("in vocabulary", &list.collisions(&vocabulary()));
+        }
+        (Some("promote"), Some(path), Some(off)) => {
+            let words = OffFile::read(off)
+                .words()
+                .iter()
+                .map(|w| w.to_string())
+                .collect::<Vec<_>>();
+            let mut list = DenyList::read(path);
+            let added = list.promote(&words, off);
+            journal::journal().record("deny promote", &[path], || list.write());
+            println!("{}: {} words promoted from {}", path, added, off);
+        }
+        _ => {
+            eprintln!("usage: deny normalize <list>");
+            eprintln!("       deny diff <list> <list>");
+            eprintln!("       deny merge <into> <from>");
+            eprintln!("       deny collisions <list>");
+            eprintln!("       deny promote <list> <word.off>");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    fn list(content: &str) -> DenyList {
+        DenyList::new("").parse(content)
+    }
+    #[test]
+    fn parse_test() {
+        let deny = list("// header\nqu\n\nha // short\n");
+        assert_eq!(deny.comments, vec!["// header"]);
+        assert_eq!(deny.words(), vec!["qu", "ha"]);
+        assert_eq!(deny.entries[1].note, Some("short".to_owned()));
+    }
+    #[test]
+    fn comments_test() {
+        let content = "// header\nqu\n// names\nharry // character\n// end\n";
+        let deny = list(content);
+        assert_eq!(deny.entries[1].comments, vec!["// names"]);
+        assert_eq!(deny.trailing, vec!["// end"]);
+        assert_eq!(deny.content(), content);
+        let deny = deny.merge(list("qu\n// names\nHarry\n// from off\nas\n"));
+        assert_eq!(
+            deny.content(),
+            "// header\n// from off\nas\n// names\nharry // character\nqu\n// end\n"
+        );
+    }
+    #[test]
+    fn normalize_test() {
+        let deny = list("Qu\nha // a\nqu\nCANCIO\u{301}N\nha // b\n").normalize();
+        assert_eq!(deny.words(), vec!["canción", "ha", "qu"]);
+        assert_eq!(deny.entries[1].note, Some("a; b".to_owned()));
+    }
+    #[test]
+    fn diff_test() {
+        let diff = list("qu\nha\n").diff(&list("HA\nas\n"));
+        assert_eq!(diff.left, vec!["qu"]);
+        assert_eq!(diff.right, vec!["as"]);
+        assert_eq!(diff.both, vec!["ha"]);
+    }
+    #[test]
+    fn merge_test() {
+        let deny = list("// one\nqu\n").merge(list("// two\nas\nqu\n"));
+        assert_eq!(deny.comments, vec!["// one", "// two"]);
+        assert_eq!(deny.words(), vec!["as", "qu"]);
+    }
+    #[test]
+    fn collisions_test() {
+        let vocabulary = vec!["casa".to_owned(), "Perro".to_owned()];
+        let deny = list("perro\nqu\n");
+        assert_eq!(deny.collisions(&vocabulary), vec!["perro"]);
+    }
+    #[test]
+    fn promote_test() {
+        let mut deny = list("qu\n");
+        let words: Vec<String> = ["qu", "Harry", "harry", "HARRY"]
+            .iter()
+            .map(|w| w.to_string())
+            .collect();
+        let added = deny.promote(&words, "word.off");
+        assert_eq!(added, 1);
+        assert_eq!(deny.entries.len(), 2);
+        assert_eq!(deny.entries[1].line(), "harry // from word.off");
+    }
+}
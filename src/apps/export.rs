@@ -0,0 +1,343 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::{
+    collections::{BTreeMap, HashMap},
+    path::Path,
+    str::FromStr,
+};
+
+use crate::apps::{booktore, level, level::Level, store::VocabularyStore, Vocabulary};
+
+const CSV_HEADER: [&str; 8] = [
+    "folder",
+    "tipo",
+    "line",
+    "word",
+    "rank",
+    "level",
+    "frequency",
+    "examples",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    /// `{ folder: { tipo: [entry] } }`
+    Json,
+    /// `[record]`
+    JsonFlat,
+    /// One record per line.
+    JsonLines,
+    Csv,
+}
+
+impl Format {
+    pub fn ext(&self) -> &str {
+        match self {
+            Format::Json | Format::JsonFlat => "json",
+            Format::JsonLines => "jsonl",
+            Format::Csv => "csv",
+        }
+    }
+
+    /// Guesses the format of an exported file from its extension and, for
+    /// `.json`, from whether it holds an object or an array.
+    pub fn detect(path: &Path, content: &str) -> Option<Self> {
+        match path.extension()?.to_str()? {
+            "csv" => Some(Format::Csv),
+            "jsonl" => Some(Format::JsonLines),
+            "json" if content.trim_start().starts_with('[') => Some(Format::JsonFlat),
+            "json" => Some(Format::Json),
+            _ => None,
+        }
+    }
+}
+
+impl FromStr for Format {
+    type Err = String;
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        match input {
+            "json" => Ok(Format::Json),
+            "json-flat" => Ok(Format::JsonFlat),
+            "jsonl" => Ok(Format::JsonLines),
+            "csv" => Ok(Format::Csv),
+            other => Err(format!(
+                "({}) is not a format, use json, json-flat, jsonl or csv",
+                other
+            )),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Record {
+    pub folder: String,
+    pub tipo: String,
+    pub line: usize,
+    pub word: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rank: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub level: Option<Level>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub frequency: Option<usize>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub examples: Vec<String>,
+}
+
+impl Record {
+    fn sort_key(&self) -> (Option<usize>, &str, &str, usize) {
+        (
+            self.folder.parse().ok(),
+            &self.folder,
+            &self.tipo,
+            self.line,
+        )
+    }
+
+    fn csv_row(&self) -> Vec<String> {
+        vec![
+            self.folder.to_owned(),
+            self.tipo.to_owned(),
+            self.line.to_string(),
+            self.word.to_owned(),
+            self.rank.map_or(String::new(), |n| n.to_string()),
+            self.level.map_or(String::new(), |n| n.to_string()),
+            self.frequency.map_or(String::new(), |n| n.to_string()),
+            self.examples.join("\n"),
+        ]
+    }
+
+    fn from_csv_row(row: &csv::StringRecord) -> Result<Self, String> {
+        let get = |index: usize| row.get(index).unwrap_or("").trim();
+        let number = |index: usize| -> Result<Option<usize>, String> {
+            match get(index) {
+                "" => Ok(None),
+                n => n
+                    .parse()
+                    .map(Some)
+                    .map_err(|_| format!("({}) is not a number in {:?}", n, row)),
+            }
+        };
+        let level = match get(5) {
+            "" => None,
+            n => Some(n.parse()?),
+        };
+        Ok(Self {
+            folder: get(0).to_owned(),
+            tipo: get(1).to_owned(),
+            line: number(2)?.unwrap_or(0),
+            word: get(3).to_owned(),
+            rank: number(4)?,
+            level,
+            frequency: number(6)?,
+            examples: booktore::examples(get(7)),
+        })
+    }
+}
+
+impl Vocabulary {
+    /// Flat list of every word, ordered by folder, tipo and line. `store` is
+    /// the optional booktore corpus used to fill frequency and examples.
+    pub fn records(&self, store: Option<&HashMap<String, (usize, String)>>) -> Vec<Record> {
+        let mut acc: Vec<_> = self
+            .words()
+            .iter()
+            .map(|mas| {
+                let corpus = store.and_then(|store| store.get(mas.word.as_str()));
+                Record {
+                    folder: mas.folder.to_owned(),
+                    tipo: mas.tipo.to_owned(),
+                    line: mas.line,
+                    word: mas.word.to_string(),
+                    rank: mas.rank,
+                    level: mas.level,
+                    frequency: corpus.map(|(count, _)| *count),
+                    examples: corpus.map_or(vec![], |(_, s)| booktore::examples(s)),
+                }
+            })
+            .collect();
+        acc.sort_by(|a, b| a.sort_key().cmp(&b.sort_key()));
+        acc
+    }
+
+    pub fn export(
+        &self,
+        format: Format,
+        store: Option<&HashMap<String, (usize, String)>>,
+    ) -> String {
+        write(&self.records(store), format)
+    }
+}
+
+pub fn write(records: &[Record], format: Format) -> String {
+    match format {
+        Format::Json => {
+            let mut store: BTreeMap<&str, BTreeMap<&str, Vec<Value>>> = BTreeMap::new();
+            for record in records {
+                let mut value = serde_json::to_value(record).unwrap();
+                if let Value::Object(map) = &mut value {
+                    map.remove("folder");
+                    map.remove("tipo");
+                }
+                store
+                    .entry(&record.folder)
+                    .or_default()
+                    .entry(&record.tipo)
+                    .or_default()
+                    .push(value);
+            }
+            format!("{}\n", serde_json::to_string_pretty(&store).unwrap())
+        }
+        Format::JsonFlat => format!("{}\n", serde_json::to_string_pretty(records).unwrap()),
+        Format::JsonLines => records.iter().fold(String::new(), |mut acc, record| {
+            acc.push_str(&serde_json::to_string(record).unwrap());
+            acc.push('\n');
+            acc
+        }),
+        Format::Csv => {
+            let mut writer = csv::Writer::from_writer(vec![]);
+            writer.write_record(CSV_HEADER).unwrap();
+            for record in records {
+                writer.write_record(record.csv_row()).unwrap();
+            }
+            String::from_utf8(writer.into_inner().unwrap()).unwrap()
+        }
+    }
+}
+
+pub fn read(content: &str, format: Format) -> Result<Vec<Record>, String> {
+    let error = |e: serde_json::Error| e.to_string();
+    match format {
+        Format::Json => {
+            let store: BTreeMap<String, BTreeMap<String, Vec<Map<String, Value>>>> =
+                serde_json::from_str(content).map_err(error)?;
+            let mut acc = vec![];
+            for (folder, types) in store {
+                for (tipo, list) in types {
+                    for mut map in list {
+                        map.insert("folder".to_owned(), Value::String(folder.to_owned()));
+                        map.insert("tipo".to_owned(), Value::String(tipo.to_owned()));
+                        acc.push(serde_json::from_value(Value::Object(map)).map_err(error)?);
+                    }
+                }
+            }
+            acc.sort_by(|a: &Record, b| a.sort_key().cmp(&b.sort_key()));
+            Ok(acc)
+        }
+        Format::JsonFlat => serde_json::from_str(content).map_err(error),
+        Format::JsonLines => content
+            .split('\n')
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| serde_json::from_str(line).map_err(error))
+            .collect(),
+        Format::Csv => csv::Reader::from_reader(content.as_bytes())
+            .records()
+            .map(|row| Record::from_csv_row(&row.map_err(|e| e.to_string())?))
+            .collect(),
+    }
+}
+
+/// Writes `records` back into `store`. Empty lines are kept so every word
+/// lands on its recorded line, and the levels of ranked words are stored as
+/// well.
+pub fn rebuild(store: &mut dyn VocabularyStore, records: &[Record]) {
+    let mut files: BTreeMap<(&str, &str), Vec<&Record>> = BTreeMap::new();
+    let mut levels = HashMap::new();
+    for record in records {
+        files
+            .entry((&record.folder, &record.tipo))
+            .or_default()
+            .push(record);
+        if let (Some(rank), Some(level)) = (record.rank, record.level) {
+            levels.insert(record.word.to_owned(), (rank, level));
+        }
+    }
+    for ((folder, tipo), mut list) in files {
+        list.sort_by_key(|record| record.line);
+        let mut lines: Vec<String> = vec![];
+        for record in list {
+            while lines.len() + 1 < record.line {
+                lines.push(String::new());
+            }
+            lines.push(record.word.to_owned());
+        }
+        store.write(folder, tipo, &lines);
+    }
+    if !levels.is_empty() {
+        store.write_levels(&level::content(&levels));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::apps::store::MemoryStore;
+    fn records() -> Vec<Record> {
+        let record = |folder: &str, tipo: &str, line: usize, word: &str| Record {
+            folder: folder.to_owned(),
+            tipo: tipo.to_owned(),
+            line,
+            word: word.to_owned(),
+            rank: None,
+            level: None,
+            frequency: None,
+            examples: vec![],
+        };
+        let mut casa = record("4", "esencial", 1, "casa");
+        casa.rank = Some(12);
+        casa.level = Some(Level::A1);
+        casa.frequency = Some(300);
+        casa.examples = vec![
+            "la, casa grande".to_owned(),
+            "mi, \"casa\", roja".to_owned(),
+        ];
+        vec![
+            casa,
+            record("4", "esencial", 3, "gato"),
+            record("5", "ANY", 1, "perro"),
+        ]
+    }
+    #[test]
+    fn round_trip_test() {
+        for format in [
+            Format::Json,
+            Format::JsonFlat,
+            Format::JsonLines,
+            Format::Csv,
+        ] {
+            let content = write(&records(), format);
+            assert_eq!(read(&content, format), Ok(records()), "{:?}", format);
+        }
+    }
+    #[test]
+    fn json_nested_test() {
+        let value: Value = serde_json::from_str(&write(&records(), Format::Json)).unwrap();
+        assert_eq!(value["4"]["esencial"][1]["word"], "gato");
+        assert!(value["4"]["esencial"][1].get("tipo").is_none());
+        assert_eq!(value["4"]["esencial"][0]["level"], "A1");
+    }
+    #[test]
+    fn detect_test() {
+        let detect = |name: &str, content: &str| Format::detect(Path::new(name), content);
+        assert_eq!(detect("a.csv", ""), Some(Format::Csv));
+        assert_eq!(detect("a.json", " [ ]"), Some(Format::JsonFlat));
+        assert_eq!(detect("a.json", "{}"), Some(Format::Json));
+        assert_eq!(detect("a.jsonl", ""), Some(Format::JsonLines));
+        assert_eq!(detect("a.txt", ""), None);
+    }
+    #[test]
+    fn rebuild_test() {
+        let mut store = MemoryStore::new(&["esencial", "ANY"]);
+        rebuild(&mut store, &records());
+        assert_eq!(store.read("4", "esencial"), vec!["casa", "", "gato"]);
+        assert_eq!(store.levels, "12,casa,A1\n");
+        let vocabulary = Vocabulary::from_store(Box::new(store));
+        assert_eq!(vocabulary.records(None).len(), 3);
+        assert_eq!(vocabulary.level("casa"), Some(Level::A1));
+    }
+    #[test]
+    fn csv_error_test() {
+        let content = "folder,tipo,line,word\n4,esencial,x,casa\n";
+        assert!(read(content, Format::Csv).is_err());
+    }
+}
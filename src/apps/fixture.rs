@@ -0,0 +1,147 @@
+use super::token;
+use super::{
+    store::{sort_folders, DirStore, MemoryStore, VocabularyStore},
+    Config, Folders, ReadParseFile, Rename, Sphere, Types, Vocabulary,
+};
+use std::path::{Path, PathBuf};
+
+/// Builds a real [`Sphere`] from synthetic config and words kept in a
+/// [`MemoryStore`], so tests never copy or change the working directory.
+///
+/// ```
+/// use gamo::apps::fixture::Fixture;
+/// let sphere = Fixture::new()
+///     .types(&["ANY", "esencial"])
+///     .words("ANY", &["casa", "perro"])
+///     .sphere();
+/// assert_eq!(sphere.vocabulary.core_data("4", "ANY").len(), 1);
+/// ```
+#[derive(Debug, Clone)]
+pub struct Fixture {
+    rename: String,
+    store: MemoryStore,
+}
+
+impl Default for Fixture {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Fixture {
+    pub fn new() -> Self {
+        Self {
+            rename: String::new(),
+            store: MemoryStore::default(),
+        }
+    }
+
+    pub fn folders(mut self, folders: &[&str]) -> Self {
+        for folder in folders {
+            if !self.store.folders.iter().any(|n| n == folder) {
+                self.store.folders.push(folder.to_string());
+            }
+        }
+        sort_folders(&mut self.store.folders);
+        self
+    }
+
+    pub fn types(mut self, types: &[&str]) -> Self {
+        self.store.types = types.iter().map(|tipo| tipo.to_string()).collect();
+        self
+    }
+
+    /// Content of `rename.on`, comments first and then `from: x to: y` lines.
+    pub fn rename(mut self, content: &str) -> Self {
+        self.rename = content.to_owned();
+        self
+    }
+
+    /// Appends `words` to `<folder>/<tipo>` where the folder is the length of
+    /// each word in letters, like `import` does.
+    pub fn words(mut self, tipo: &str, words: &[&str]) -> Self {
+        for word in words {
+            let folder = token::folder(word);
+            self = self.line(&folder, tipo, word);
+        }
+        self
+    }
+
+    /// Appends one line to `<folder>/<tipo>`, blank lines included.
+    pub fn line(mut self, folder: &str, tipo: &str, line: &str) -> Self {
+        let mut lines = self.store.read(folder, tipo);
+        lines.push(line.to_owned());
+        self.store.write(folder, tipo, &lines);
+        self
+    }
+
+    pub fn store(&self) -> MemoryStore {
+        self.store.to_owned()
+    }
+
+    pub fn vocabulary(self) -> Vocabulary {
+        Vocabulary::from_store(Box::new(self.store))
+    }
+
+    /// The sphere `setup` would give for this config, with the renames
+    /// already applied.
+    pub fn sphere(self) -> Sphere {
+        let mut config = Config::new();
+        config.folders = Folders::new();
+        config.folders.list = self.store.folders();
+        config.types = Types::new();
+        config.types.list = self.store.types();
+        let content = self.rename.to_owned();
+        config.rename = ReadParseFile { content }.split_rename(Rename::new());
+        let mut sphere = Sphere {
+            current_dir: PathBuf::new(),
+            config,
+            vocabulary: self.vocabulary(),
+        };
+        sphere.rename_files();
+        sphere
+    }
+}
+
+/// Loads the `config` and `vocabulary` of `dir` without creating, renaming
+/// or writing anything.
+pub fn open<T: AsRef<Path>>(dir: T) -> Sphere {
+    let current_dir = dir.as_ref().to_path_buf();
+    let config = Config::new().setup(&current_dir.join("config"));
+    let mut vocabulary = Vocabulary::new();
+    vocabulary.name = "vocabulary".to_owned();
+    vocabulary.dir = current_dir.join(&vocabulary.name);
+    vocabulary.store = Box::new(DirStore::new(&vocabulary.dir).config(&config.dir));
+    vocabulary.reload();
+    Sphere {
+        current_dir,
+        config,
+        vocabulary,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn sphere_test() {
+        let sphere = Fixture::new()
+            .folders(&["6"])
+            .types(&["ANY", "esencial"])
+            .rename("// any is esencial\nfrom: any to: esencial")
+            .words("ANY", &["casa", "perro"])
+            .words("any", &["gato"])
+            .sphere();
+        assert_eq!(sphere.config.folders.list, vec!["4", "5", "6"]);
+        assert_eq!(sphere.config.rename.comments, vec!["// any is esencial"]);
+        assert!(sphere.config.rename.changes.is_empty());
+        let words: Vec<_> = sphere
+            .vocabulary
+            .core_data("4", "esencial")
+            .iter()
+            .map(|mas| mas.word.to_string())
+            .collect();
+        assert_eq!(words, vec!["gato"]);
+        assert!(sphere.vocabulary.store.read("4", "any").is_empty());
+    }
+}
@@ -0,0 +1,313 @@
+use serde_json::Value;
+use std::{
+    collections::{BTreeMap, HashSet},
+    fmt, fs,
+    path::{Path, PathBuf},
+};
+
+use crate::apps::{booktore, case::Casing, intern::Word, off::OffFile, token, Mas, Vocabulary};
+
+/// Keeps the corpus casing of proper nouns, "España" instead of "españa".
+const TRUECASE: bool = true;
+/// Tipo proper nouns are filed under instead of the requested one, it must
+/// be listed in `types.on`.
+const PROPER_TIPO: Option<&str> = None;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Reason {
+    Invalid,
+    Duplicate,
+    InVocabulary,
+    InOff,
+}
+
+impl fmt::Display for Reason {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let reason = match self {
+            Reason::Invalid => "not a valid word",
+            Reason::Duplicate => "repeated in the input",
+            Reason::InVocabulary => "already in the vocabulary",
+            Reason::InOff => "rejected in word.off",
+        };
+        write!(f, "{}", reason)
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct Import {
+    pub added: Vec<Mas>,
+    pub skipped: Vec<(String, Reason)>,
+}
+
+impl Import {
+    /// Decides where every word of `words` goes without touching any file.
+    /// `casing` gives the spelling and tipo of proper nouns, words are
+    /// compared ignoring case.
+    pub fn plan(
+        words: &[String],
+        tipo: &str,
+        vocabulary: &Vocabulary,
+        off: &[String],
+        casing: &Casing,
+    ) -> Self {
+        let known: HashSet<String> = vocabulary
+            .words()
+            .iter()
+            .map(|mas| mas.word.to_lowercase())
+            .collect();
+        let off: HashSet<&str> = off.iter().map(|word| word.as_str()).collect();
+        let mut lines: BTreeMap<(String, String), usize> = BTreeMap::new();
+        let mut seen = HashSet::new();
+        let mut import = Import::default();
+
+        for input in words {
+            let word = match token::word(input) {
+                Some(word) => word,
+                None => {
+                    import.skipped.push((input.to_owned(), Reason::Invalid));
+                    continue;
+                }
+            };
+            let reason = if known.contains(&word) {
+                Some(Reason::InVocabulary)
+            } else if off.contains(word.as_str()) {
+                Some(Reason::InOff)
+            } else if !seen.insert(word.to_owned()) {
+                Some(Reason::Duplicate)
+            } else {
+                None
+            };
+            if let Some(reason) = reason {
+                import.skipped.push((word, reason));
+                continue;
+            }
+
+            let (word, tipo) = casing.route(&word, tipo);
+            let folder = token::folder(&word);
+            let key = (folder.to_owned(), tipo.to_owned());
+            let line = lines.entry(key).or_insert_with(|| {
+                vocabulary
+                    .core_data(&folder, &tipo)
+                    .iter()
+                    .map(|mas| mas.line)
+                    .max()
+                    .unwrap_or(0)
+            });
+            *line += 1;
+            let mut mas = Mas::new().setup(&folder, &tipo);
+            mas.line = *line;
+            mas.word = Word::new(&word);
+            import.added.push(mas);
+        }
+        import
+    }
+
+    /// Appends the planned words to their folder and tipo list in the
+    /// vocabulary store, which registers new length folders.
+    pub fn apply(&self, vocabulary: &mut Vocabulary) {
+        let mut files: BTreeMap<(&str, &str), Vec<&Mas>> = BTreeMap::new();
+        for mas in &self.added {
+            files.entry((&mas.folder, &mas.tipo)).or_default().push(mas);
+        }
+
+        for ((folder, tipo), list) in files {
+            let mut lines = vocabulary.store.read(folder, tipo);
+            while lines.last().is_some_and(|line| line.is_empty()) {
+                lines.pop();
+            }
+            for mas in list {
+                while lines.len() + 1 < mas.line {
+                    lines.push(String::new());
+                }
+                lines.push(mas.word.to_string());
+            }
+            vocabulary.store.write(folder, tipo, &lines);
+        }
+        for mas in &self.added {
+            vocabulary.push(mas.clone());
+        }
+    }
+
+    pub fn report(&self) {
+        for mas in &self.added {
+            println!("added {} to {}/{}.on", mas.word, mas.folder, mas.tipo);
+        }
+        for (word, reason) in &self.skipped {
+            println!("skipped {}: {}", word, reason);
+        }
+        println!(
+            "Import: {} added, {} skipped",
+            self.added.len(),
+            self.skipped.len()
+        );
+    }
+}
+
+fn collect_words(value: &Value, acc: &mut Vec<String>) {
+    match value {
+        Value::String(word) => acc.push(word.to_owned()),
+        Value::Array(list) => list.iter().for_each(|n| collect_words(n, acc)),
+        Value::Object(map) => match map.get("word") {
+            Some(Value::String(word)) => acc.push(word.to_owned()),
+            _ => map.values().for_each(|n| collect_words(n, acc)),
+        },
+        _ => {}
+    }
+}
+
+/// Reads the words of a plain list, a CSV file (its `word` column, or the
+/// first one) or a JSON / JSON Lines file, including `export` output.
+pub fn read_words(path: &Path) -> Result<Vec<String>, String> {
+    let content = fs::read_to_string(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+    let error = |e: &dyn fmt::Display| format!("{}: {}", path.display(), e);
+    let mut acc = vec![];
+    match path.extension().and_then(|ext| ext.to_str()) {
+        Some("csv") => {
+            let mut reader = csv::Reader::from_reader(content.as_bytes());
+            let headers = reader.headers().map_err(|e| error(&e))?.clone();
+            let column = headers.iter().position(|n| n == "word").unwrap_or(0);
+            for row in reader.records() {
+                let row = row.map_err(|e| error(&e))?;
+                acc.extend(row.get(column).map(|word| word.to_owned()));
+            }
+        }
+        Some("json") => {
+            let value: Value = serde_json::from_str(&content).map_err(|e| error(&e))?;
+            collect_words(&value, &mut acc);
+        }
+        Some("jsonl") => {
+            for line in content.split('\n').filter(|n| !n.trim().is_empty()) {
+                let value: Value = serde_json::from_str(line).map_err(|e| error(&e))?;
+                collect_words(&value, &mut acc);
+            }
+        }
+        _ => {
+            for line in content.split('\n').map(|line| line.trim()) {
+                if !line.starts_with("//") {
+                    acc.extend(line.split_whitespace().map(|word| word.to_owned()));
+                }
+            }
+        }
+    }
+    Ok(acc)
+}
+
+/// The `word.off` that belongs to `vocabulary`.
+pub fn off_path(vocabulary: &Vocabulary) -> PathBuf {
+    vocabulary.dir.with_file_name("word.off")
+}
+
+/// The casing imported words get, see `TRUECASE` and `PROPER_TIPO`.
+pub fn casing() -> Casing {
+    let casing = match TRUECASE {
+        true => booktore::init_casing(),
+        false => Casing::default(),
+    };
+    casing.proper(PROPER_TIPO)
+}
+
+pub fn run(vocabulary: &mut Vocabulary, path: &Path, tipo: &str) -> Result<Import, String> {
+    let types = vocabulary.store.types();
+    for tipo in [Some(tipo), PROPER_TIPO].into_iter().flatten() {
+        if !types.iter().any(|n| n == tipo) {
+            return Err(format!("({}) is not a tipo, use one of {:?}", tipo, types));
+        }
+    }
+    let words = read_words(path)?;
+    let off: Vec<String> = OffFile::read(off_path(vocabulary))
+        .words()
+        .iter()
+        .filter_map(|word| token::word(word))
+        .collect();
+    let import = Import::plan(&words, tipo, vocabulary, &off, &casing());
+    import.apply(vocabulary);
+    Ok(import)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::apps::store::{MemoryStore, VocabularyStore};
+    fn vocabulary() -> Vocabulary {
+        let mut casa = Mas::new().setup(&"4".to_owned(), &"esencial".to_owned());
+        casa.word = Word::new("casa");
+        casa.line = 2;
+        let mut vocabulary = Vocabulary::new();
+        vocabulary.push(casa);
+        vocabulary
+    }
+    fn words(list: &[&str]) -> Vec<String> {
+        list.iter().map(|w| w.to_string()).collect()
+    }
+    #[test]
+    fn plan_test() {
+        let input = words(&["Casa", "gato,", "x", "Perro", "gato", "Canción", "loro"]);
+        let casing = Casing::default();
+        let import = Import::plan(
+            &input,
+            "esencial",
+            &vocabulary(),
+            &words(&["loro"]),
+            &casing,
+        );
+        let added: Vec<_> = import
+            .added
+            .iter()
+            .map(|mas| (mas.word.as_str(), mas.folder.as_str(), mas.line))
+            .collect();
+        assert_eq!(
+            added,
+            vec![("gato", "4", 3), ("perro", "5", 1), ("canción", "7", 1)]
+        );
+        assert_eq!(
+            import.skipped,
+            vec![
+                ("casa".to_owned(), Reason::InVocabulary),
+                ("x".to_owned(), Reason::Invalid),
+                ("gato".to_owned(), Reason::Duplicate),
+                ("loro".to_owned(), Reason::InOff),
+            ]
+        );
+    }
+    #[test]
+    fn apply_test() {
+        let mut store = MemoryStore::new(&["esencial"]);
+        store.write("4", "esencial", &words(&["", "casa", ""]));
+        let mut vocabulary = Vocabulary::from_store(Box::new(store));
+        let input = words(&["gato", "perro"]);
+        let import = Import::plan(&input, "esencial", &vocabulary, &[], &Casing::default());
+        import.apply(&mut vocabulary);
+        assert_eq!(
+            vocabulary.store.read("4", "esencial"),
+            words(&["", "casa", "gato"])
+        );
+        assert_eq!(vocabulary.store.folders(), vec!["4", "5"]);
+        assert_eq!(vocabulary.core_data("5", "esencial")[0].line, 1);
+    }
+    #[test]
+    fn proper_test() {
+        let text = "Vive en Madrid. Fui a Madrid, luego a Madrid y a Madrid.";
+        let casing = Casing::new(text.split_whitespace()).proper(Some("nombres"));
+        let input = words(&["madrid", "gato", "CASA"]);
+        let import = Import::plan(&input, "esencial", &vocabulary(), &[], &casing);
+        let added: Vec<_> = import
+            .added
+            .iter()
+            .map(|mas| (mas.word.as_str(), mas.tipo.as_str(), mas.line))
+            .collect();
+        assert_eq!(
+            added,
+            vec![("Madrid", "nombres", 1), ("gato", "esencial", 3)]
+        );
+        assert_eq!(import.skipped[0], ("casa".to_owned(), Reason::InVocabulary));
+    }
+    #[test]
+    fn collect_words_test() {
+        let value: Value =
+            serde_json::from_str(r#"["uno", {"word": "dos"}, {"5": {"ANY": [{"word": "tres"}]}}]"#)
+                .unwrap();
+        let mut acc = vec![];
+        collect_words(&value, &mut acc);
+        assert_eq!(acc, vec!["uno", "dos", "tres"]);
+    }
+}
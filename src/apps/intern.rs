@@ -0,0 +1,170 @@
+use std::{
+    cmp::Ordering,
+    collections::HashSet,
+    fmt,
+    hash::{Hash, Hasher},
+    ops::Deref,
+    ptr,
+    sync::{OnceLock, RwLock},
+};
+
+/// Every interned string, stored once for the whole process. Strings are
+/// never freed, so a `Word` can point at its string directly and only
+/// creating one takes the lock.
+fn interner() -> &'static RwLock<HashSet<&'static str>> {
+    static INTERNER: OnceLock<RwLock<HashSet<&'static str>>> = OnceLock::new();
+    INTERNER.get_or_init(Default::default)
+}
+
+/// Interned word shared by the vocabulary, `mas` and the booktore corpus.
+/// Comparing and hashing a `Word` only touches its address.
+#[derive(Clone, Copy)]
+pub struct Word(&'static str);
+
+impl Word {
+    pub fn new(word: &str) -> Self {
+        if let Some(word) = Word::get(word) {
+            return word;
+        }
+        let mut set = interner().write().unwrap();
+        Word::insert(&mut set, word)
+    }
+
+    /// The word for `word` when it was already interned, without adding it.
+    pub fn get(word: &str) -> Option<Self> {
+        let set = interner().read().unwrap();
+        set.get(word).copied().map(Word)
+    }
+
+    /// Interns a whole list taking the lock once.
+    pub fn list<'a, T: IntoIterator<Item = &'a str>>(words: T) -> Vec<Self> {
+        let mut set = interner().write().unwrap();
+        words
+            .into_iter()
+            .map(|word| Word::insert(&mut set, word))
+            .collect()
+    }
+
+    fn insert(set: &mut HashSet<&'static str>, word: &str) -> Self {
+        let word = match set.get(word).copied() {
+            Some(word) => word,
+            None => {
+                let word: &'static str = Box::leak(word.into());
+                set.insert(word);
+                word
+            }
+        };
+        Word(word)
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        self.0
+    }
+}
+
+impl PartialEq for Word {
+    fn eq(&self, other: &Self) -> bool {
+        ptr::eq(self.0, other.0)
+    }
+}
+
+impl Eq for Word {}
+
+impl Hash for Word {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        ptr::hash(self.0, state)
+    }
+}
+
+impl Deref for Word {
+    type Target = str;
+    fn deref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl Default for Word {
+    fn default() -> Self {
+        Word::new("")
+    }
+}
+
+impl From<&str> for Word {
+    fn from(word: &str) -> Self {
+        Word::new(word)
+    }
+}
+
+impl PartialEq<str> for Word {
+    fn eq(&self, other: &str) -> bool {
+        self.as_str() == other
+    }
+}
+
+impl PartialEq<&str> for Word {
+    fn eq(&self, other: &&str) -> bool {
+        self.as_str() == *other
+    }
+}
+
+/// Alphabetical, like the strings themselves.
+impl Ord for Word {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match self == other {
+            true => Ordering::Equal,
+            false => self.as_str().cmp(other.as_str()),
+        }
+    }
+}
+
+impl PartialOrd for Word {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl fmt::Display for Word {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+impl fmt::Debug for Word {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:?}", self.as_str())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn word_test() {
+        let casa = Word::new("casa");
+        assert_eq!(casa, Word::new("casa"));
+        assert_ne!(casa, Word::new("cosa"));
+        assert_eq!(casa.as_str(), "casa");
+        assert_eq!(casa, "casa");
+        assert_eq!(casa.len(), 4);
+        assert_eq!(format!("{} {:?}", casa, casa), "casa \"casa\"");
+        assert!(Word::new("perro") > casa);
+    }
+    #[test]
+    fn get_test() {
+        assert_eq!(Word::get("intern-get-test"), None);
+        let list = Word::list(["intern-get-test", "casa", "intern-get-test"]);
+        assert_eq!(list[0], list[2]);
+        assert_eq!(Word::get("intern-get-test"), Some(list[0]));
+    }
+    #[test]
+    fn threads_test() {
+        let list: Vec<Word> = std::thread::scope(|scope| {
+            let handles: Vec<_> = (0..4)
+                .map(|_| scope.spawn(|| Word::new("intern-threads-test")))
+                .collect();
+            handles.into_iter().map(|n| n.join().unwrap()).collect()
+        });
+        assert!(list.iter().all(|word| *word == list[0]));
+        assert_eq!(list[0].as_str(), "intern-threads-test");
+    }
+}
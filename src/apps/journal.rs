@@ -0,0 +1,294 @@
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    fmt, fs,
+    io::Write,
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use crate::apps::checksum;
+
+/// Records every command that changes the vocabulary, `word.on`, `word.off`
+/// or the parts so it can be undone.
+const JOURNAL: bool = true;
+const DIR: &str = "journal";
+
+/// One file an operation touched, `None` when it did not exist.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Change {
+    pub path: PathBuf,
+    pub before: Option<String>,
+    pub after: Option<String>,
+}
+
+/// One line of `journal.jsonl`. An `undo` lists the operations it reverted
+/// in `reverts`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Entry {
+    pub id: usize,
+    /// Seconds since the Unix epoch.
+    pub time: u64,
+    pub command: String,
+    pub changes: Vec<Change>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub reverts: Vec<usize>,
+}
+
+impl fmt::Display for Entry {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "#{} {}", self.id, self.command)?;
+        for change in &self.changes {
+            let state = match (&change.before, &change.after) {
+                (None, _) => "created",
+                (_, None) => "deleted",
+                _ => "changed",
+            };
+            write!(f, "\n  {} {}", state, change.path.display())?;
+        }
+        Ok(())
+    }
+}
+
+/// Hash and content of every file under some paths.
+type Snapshot = BTreeMap<PathBuf, (String, Vec<u8>)>;
+
+fn walk(path: &Path, snapshot: &mut Snapshot) {
+    if path.is_dir() {
+        let mut list: Vec<PathBuf> = fs::read_dir(path)
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .collect();
+        list.sort();
+        for path in list {
+            walk(&path, snapshot);
+        }
+    } else if let Ok(content) = fs::read(path) {
+        let hash = checksum::hex(&content);
+        snapshot.insert(path.to_path_buf(), (hash, content));
+    }
+}
+
+fn hash_of(path: &Path) -> Option<String> {
+    fs::read(path).ok().map(|content| checksum::hex(&content))
+}
+
+/// Append-only `journal.jsonl` next to an `objects` folder holding the
+/// previous content of every changed file, named by its hash.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Journal {
+    dir: PathBuf,
+}
+
+/// The journal of the working directory.
+pub fn journal() -> Journal {
+    Journal::new(DIR)
+}
+
+impl Journal {
+    pub fn new<T: AsRef<Path>>(dir: T) -> Self {
+        Self {
+            dir: dir.as_ref().to_path_buf(),
+        }
+    }
+
+    fn object(&self, hash: &str) -> PathBuf {
+        self.dir.join("objects").join(hash)
+    }
+
+    /// Every entry in the order they were recorded.
+    pub fn entries(&self) -> Vec<Entry> {
+        let content = fs::read_to_string(self.dir.join("journal.jsonl")).unwrap_or_default();
+        content
+            .lines()
+            .filter_map(|line| serde_json::from_str(line).ok())
+            .collect()
+    }
+
+    fn append(&self, command: &str, changes: Vec<Change>, reverts: Vec<usize>) -> Entry {
+        let time = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+        let id = self.entries().last().map_or(1, |entry| entry.id + 1);
+        let entry = Entry {
+            id,
+            time,
+            command: command.to_owned(),
+            changes,
+            reverts,
+        };
+        fs::create_dir_all(&self.dir).unwrap();
+        let mut file = fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(self.dir.join("journal.jsonl"))
+            .unwrap();
+        writeln!(file, "{}", serde_json::to_string(&entry).unwrap()).unwrap();
+        entry
+    }
+
+    fn changes(&self, before: Snapshot, after: &Snapshot) -> Vec<Change> {
+        let mut paths: Vec<&PathBuf> = before.keys().chain(after.keys()).collect();
+        paths.sort();
+        paths.dedup();
+        let mut changes = vec![];
+        for path in paths {
+            let old = before.get(path);
+            let new = after.get(path).map(|(hash, _)| hash.to_owned());
+            if old.map(|(hash, _)| hash) == new.as_ref() {
+                continue;
+            }
+            if let Some((hash, content)) = old {
+                let object = self.object(hash);
+                if !object.exists() {
+                    fs::create_dir_all(object.parent().unwrap()).unwrap();
+                    fs::write(object, content).unwrap();
+                }
+            }
+            changes.push(Change {
+                path: path.to_owned(),
+                before: old.map(|(hash, _)| hash.to_owned()),
+                after: new,
+            });
+        }
+        changes
+    }
+
+    /// Runs `f` and records how it changed the files under `paths`, nothing
+    /// when it changed none.
+    pub fn record<P, T, F>(&self, command: &str, paths: &[P], f: F) -> T
+    where
+        P: AsRef<Path>,
+        F: FnOnce() -> T,
+    {
+        if !JOURNAL {
+            return f();
+        }
+        let snapshot = |acc: &mut Snapshot| paths.iter().for_each(|path| walk(path.as_ref(), acc));
+        let mut before = Snapshot::new();
+        snapshot(&mut before);
+        let value = f();
+        let mut after = Snapshot::new();
+        snapshot(&mut after);
+        let changes = self.changes(before, &after);
+        if !changes.is_empty() {
+            self.append(command, changes, vec![]);
+        }
+        value
+    }
+
+    /// Operations that can still be undone, most recent first.
+    pub fn history(&self) -> Vec<Entry> {
+        let entries = self.entries();
+        let reverted: Vec<usize> = entries.iter().flat_map(|n| n.reverts.to_owned()).collect();
+        entries
+            .into_iter()
+            .rev()
+            .filter(|entry| entry.reverts.is_empty() && !reverted.contains(&entry.id))
+            .collect()
+    }
+
+    /// Puts back the files of the last `count` operations, failing without
+    /// touching anything when one of them was changed since.
+    pub fn undo(&self, count: usize) -> Result<Vec<Entry>, String> {
+        let list: Vec<Entry> = self.history().into_iter().take(count).collect();
+        let mut state: BTreeMap<PathBuf, Option<String>> = BTreeMap::new();
+        for entry in &list {
+            for change in &entry.changes {
+                let current = state
+                    .entry(change.path.to_owned())
+                    .or_insert_with(|| hash_of(&change.path));
+                if *current != change.after {
+                    return Err(format!(
+                        "{} changed after #{} {}, nothing was undone",
+                        change.path.display(),
+                        entry.id,
+                        entry.command
+                    ));
+                }
+                if let Some(hash) = &change.before {
+                    if !self.object(hash).exists() {
+                        let path = self.object(hash);
+                        return Err(format!("{} is missing", path.display()));
+                    }
+                }
+                *current = change.before.to_owned();
+            }
+        }
+        let mut changes = vec![];
+        for (path, hash) in state {
+            let before = hash_of(&path);
+            if before == hash {
+                continue;
+            }
+            match &hash {
+                Some(hash) => {
+                    let content = fs::read(self.object(hash)).unwrap();
+                    if let Some(parent) = path.parent() {
+                        fs::create_dir_all(parent).unwrap();
+                    }
+                    fs::write(&path, content).unwrap();
+                }
+                None => fs::remove_file(&path).unwrap(),
+            }
+            changes.push(Change {
+                path,
+                before,
+                after: hash,
+            });
+        }
+        if !list.is_empty() {
+            let reverts = list.iter().map(|entry| entry.id).collect();
+            self.append("undo", changes, reverts);
+        }
+        Ok(list)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn undo_test() {
+        let root = std::env::temp_dir().join(format!("gamo-journal-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        let words = root.join("words");
+        fs::create_dir_all(&words).unwrap();
+        fs::write(words.join("a.on"), "casa\n").unwrap();
+        let journal = Journal::new(root.join("journal"));
+
+        let paths = [&words];
+        let value = journal.record("add", &paths, || {
+            fs::write(words.join("a.on"), "casa\nperro\n").unwrap();
+            fs::write(words.join("b.on"), "gato\n").unwrap();
+            7
+        });
+        assert_eq!(value, 7);
+        journal.record("nothing", &paths, || {});
+        journal.record("clean", &paths, || {
+            fs::remove_file(words.join("a.on")).unwrap()
+        });
+        let history = journal.history();
+        assert_eq!(history.len(), 2);
+        assert_eq!(
+            history[0].to_string(),
+            format!("#2 clean\n  deleted {}", words.join("a.on").display())
+        );
+        assert_eq!(history[1].changes.len(), 2);
+
+        fs::write(words.join("b.on"), "loro\n").unwrap();
+        assert!(journal.undo(2).is_err());
+        assert!(!words.join("a.on").exists());
+        fs::write(words.join("b.on"), "gato\n").unwrap();
+
+        let undone = journal.undo(2).unwrap();
+        assert_eq!(undone.len(), 2);
+        assert_eq!(fs::read_to_string(words.join("a.on")).unwrap(), "casa\n");
+        assert!(!words.join("b.on").exists());
+        assert!(journal.history().is_empty());
+        assert_eq!(journal.entries().last().unwrap().reverts, vec![2, 1]);
+        assert!(journal.undo(1).unwrap().is_empty());
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
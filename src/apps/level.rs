@@ -0,0 +1,141 @@
+use serde::{Deserialize, Serialize};
+use std::{collections::HashMap, fmt, str::FromStr};
+
+/// Corpus rank (1 based) up to which a word belongs to each level.
+const BANDS: [(usize, Level); 5] = [
+    (1_000, Level::A1),
+    (2_000, Level::A2),
+    (5_000, Level::B1),
+    (10_000, Level::B2),
+    (20_000, Level::C1),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum Level {
+    A1,
+    A2,
+    B1,
+    B2,
+    C1,
+    C2,
+}
+
+impl Level {
+    pub fn from_rank(rank: usize) -> Self {
+        BANDS
+            .iter()
+            .find(|(limit, _)| rank <= *limit)
+            .map_or(Level::C2, |(_, level)| *level)
+    }
+
+    /// Upper rank of the frequency band, `None` for the open-ended last one.
+    pub fn band(&self) -> Option<usize> {
+        BANDS
+            .iter()
+            .find(|(_, level)| level == self)
+            .map(|(limit, _)| *limit)
+    }
+}
+
+impl fmt::Display for Level {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+impl FromStr for Level {
+    type Err = String;
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        match input.trim().to_uppercase().as_str() {
+            "A1" => Ok(Level::A1),
+            "A2" => Ok(Level::A2),
+            "B1" => Ok(Level::B1),
+            "B2" => Ok(Level::B2),
+            "C1" => Ok(Level::C1),
+            "C2" => Ok(Level::C2),
+            other => Err(format!("({}) is not a level, use A1 to C2", other)),
+        }
+    }
+}
+
+/// Corpus rank of every word, the most frequent one being 1.
+pub fn ranks(store: &HashMap<String, (usize, String)>) -> HashMap<String, usize> {
+    let mut list: Vec<_> = store
+        .iter()
+        .map(|(word, (count, _))| (word, count))
+        .collect();
+    list.sort_by(|(word_a, a), (word_b, b)| b.cmp(a).then(word_a.cmp(word_b)));
+    list.into_iter()
+        .enumerate()
+        .map(|(index, (word, _))| (word.to_owned(), index + 1))
+        .collect()
+}
+
+/// Parses a levels file made by [`content`], one `rank,word,level` per line.
+pub fn parse(content: &str) -> HashMap<String, (usize, Level)> {
+    let mut store = HashMap::new();
+    for line in content.split('\n').map(|line| line.trim()) {
+        let inner: Vec<_> = line.split(',').collect();
+        if let [rank, word, level] = inner[..] {
+            if let (Ok(rank), Ok(level)) = (rank.parse(), level.parse()) {
+                store.insert(word.to_owned(), (rank, level));
+            }
+        }
+    }
+    store
+}
+
+pub fn content(store: &HashMap<String, (usize, Level)>) -> String {
+    let mut list: Vec<_> = store.iter().collect();
+    list.sort_by(|(word_a, (a, _)), (word_b, (b, _))| a.cmp(b).then(word_a.cmp(word_b)));
+    list.iter()
+        .fold(String::new(), |mut acc, (word, (rank, level))| {
+            acc.push_str(&format!("{},{},{}\n", rank, word, level));
+            acc
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn from_rank_test() {
+        assert_eq!(Level::from_rank(1), Level::A1);
+        assert_eq!(Level::from_rank(1_000), Level::A1);
+        assert_eq!(Level::from_rank(1_001), Level::A2);
+        assert_eq!(Level::from_rank(19_999), Level::C1);
+        assert_eq!(Level::from_rank(50_000), Level::C2);
+    }
+    #[test]
+    fn band_test() {
+        assert_eq!(Level::B1.band(), Some(5_000));
+        assert_eq!(Level::C2.band(), None);
+    }
+    #[test]
+    fn parse_test() {
+        assert_eq!("b2".parse(), Ok(Level::B2));
+        assert!("D1".parse::<Level>().is_err());
+        assert_eq!(Level::C1.to_string(), "C1");
+    }
+    #[test]
+    fn ranks_test() {
+        let store: HashMap<_, _> = [("casa", 5), ("perro", 9), ("gato", 5)]
+            .into_iter()
+            .map(|(word, count)| (word.to_owned(), (count, String::new())))
+            .collect();
+        let ranks = ranks(&store);
+        assert_eq!(ranks["perro"], 1);
+        assert_eq!(ranks["casa"], 2);
+        assert_eq!(ranks["gato"], 3);
+    }
+    #[test]
+    fn content_test() {
+        let store: HashMap<_, _> = [("perro", (1, Level::A1)), ("casa", (2_500, Level::B1))]
+            .into_iter()
+            .map(|(word, rank)| (word.to_owned(), rank))
+            .collect();
+        let content = content(&store);
+        assert_eq!(content, "1,perro,A1\n2500,casa,B1\n");
+        assert_eq!(parse(&content), store);
+    }
+}
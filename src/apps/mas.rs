@@ -1,119 +1,56 @@
+use rayon::prelude::*;
+use std::collections::BTreeMap;
 use std::collections::HashMap;
 use std::collections::HashSet;
+use std::fmt;
 use std::fs;
-use std::path::Path;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
 
-use crate::apps::booktore;
+use crate::apps::booktore::{self, ExampleStore};
+use crate::apps::case::Casing;
+use crate::apps::checksum;
+use crate::apps::intern::Word;
+use crate::apps::journal;
+use crate::apps::normal;
+use crate::apps::off::OffFile;
+use crate::apps::output::{self, BuildOutput};
+use crate::apps::token::{self, rule, Str};
 const MATCH: &str = "";
 const MATCHEND: bool = true;
 const SORT_BY_POPULAR: bool = false;
 const MUST_CONTAINS_WORDS: bool = true;
+const ON_MISSING: Missing = Missing::Fail;
 const USING_BOOKTORE: bool = false;
+const UPDATE_OFF_FILE: bool = true;
+const SORT_BY_CORPUS: bool = false;
+const CORPUS_MIN_BOOKS: usize = 3;
+const CORPUS_MIN_COUNT: usize = 1;
+const TRUECASE: bool = false;
 
-type Tipo = HashSet<String>;
+type Tipo = HashSet<Word>;
 
-mod rule {
-    pub struct Word {}
-    impl Word {
-        pub fn min() -> usize {
-            2
-        }
-        pub fn max() -> usize {
-            25
-        }
-    }
-    pub fn is_min(word: &str) -> bool {
-        word.len() < Word::min()
-    }
-    pub fn is_max(word: &str) -> bool {
-        word.len() > Word::max()
-    }
-}
-struct English;
-impl English {
-    fn start() -> i32 {
-        97
-    }
-    fn end() -> i32 {
-        122
-    }
-
-    fn is_range(value: i32) -> bool {
-        value >= English::start() && value <= English::end()
-    }
-}
-struct Str;
 impl Str {
-    fn rm_start(input: &str) -> &str {
-        let mut start = 0;
-        let mut list = input.chars();
-        let mut current = list.next();
-        while let Some(letter) = current {
-            if !English::is_range(letter as i32) {
-                start += letter.len_utf8();
-            } else {
-                return &input[start..input.len()];
-            }
-            current = list.next();
-        }
-        &input[start..input.len()]
-    }
-    fn rm_end(input: &str) -> &str {
-        let mut end = Str::utf_count(input);
-        let mut list = input.chars();
-        let mut current = list.next_back();
-        while let Some(letter) = current {
-            if !English::is_range(letter as i32) {
-                end -= letter.len_utf8();
-            } else {
-                return &input[0..end];
-            }
-            current = list.next_back();
-        }
-        &input[0..end]
-    }
-    fn rm_start_end(input: &str) -> &str {
-        Str::rm_end(Str::rm_start(input))
-    }
-    fn valid_english(input: &str) -> bool {
-        for letter in input.chars() {
-            if !English::is_range(letter as i32) {
-                return false;
-            }
-        }
-        input.len() > 0
-    }
+    #[allow(dead_code)]
     pub fn is_ing(input: &str) -> bool {
-        if input.len() <= 3 {
-            return false;
-        }
-        let start = input.len() - 3;
-        &input[start..input.len()] == "ing"
+        input.len() > 3 && input.ends_with("ing")
     }
+    #[allow(dead_code)]
     pub fn is_ed(input: &str) -> bool {
-        if input.len() <= 2 {
-            return false;
-        }
-        let start = input.len() - 2;
-        &input[start..input.len()] == "ed"
+        input.len() > 2 && input.ends_with("ed")
     }
+    #[allow(dead_code)]
     pub fn is_plural(input: &str) -> bool {
-        if input.len() <= 2 {
-            return false;
-        }
-        let start = input.len() - 1;
-        let letter = &input[start..input.len()];
-        letter == "s" && &input[start - 1..start] != "s"
+        input.len() > 2 && input.ends_with('s') && !input.ends_with("ss")
     }
     pub fn is_match(input: &str) -> bool {
         if input.len() < 4 || MATCH.trim() == "" {
             return false;
         }
         if MATCHEND {
-            &input[input.len() - MATCH.len()..input.len()] == MATCH
+            input.ends_with(MATCH)
         } else {
-            &input[0..MATCH.len()] == MATCH
+            input.starts_with(MATCH)
         }
     }
     pub fn get_word(word: &str) -> Option<&str> {
@@ -122,43 +59,49 @@ impl Str {
         }
         Some(word)
     }
-    fn utf_count(input: &str) -> usize {
-        input.chars().fold(0, |acc, letter| acc + letter.len_utf8())
-    }
 }
 
 struct Parse;
 impl Parse {
+    fn popular(list: Vec<&str>) -> Vec<&str> {
+        let store = list.iter().fold(HashMap::new(), |mut store, word| {
+            if let Some(word) = store.get_mut(word) {
+                *word += 1;
+            } else {
+                store.insert(*word, 1);
+            }
+            store
+        });
+
+        let mut new_list: Vec<_> = store.into_iter().collect();
+        new_list.sort_by(|(word_a, a), (word_b, b)| b.cmp(a).then(word_a.cmp(word_b)));
+        new_list.iter().map(|(word, _)| *word).collect()
+    }
     fn sort_popular(list: Vec<&str>) -> Vec<&str> {
         if SORT_BY_POPULAR {
-            let store = list.iter().fold(HashMap::new(), |mut store, word| {
-                if let Some(word) = store.get_mut(word) {
-                    *word += 1;
-                } else {
-                    store.insert(*word, 1);
-                }
-                store
-            });
-
-            let mut new_list: Vec<_> = store.into_iter().collect();
-            new_list.sort_by(|(_, a), (_, b)| a.cmp(b));
-            new_list.reverse();
-            return new_list.iter().map(|(word, _)| *word).collect();
+            return Parse::popular(list);
         }
         list
     }
+    fn token(word: &str) -> Option<String> {
+        let word = word.trim().to_lowercase();
+        let word = Str::rm_start_end(&word);
+        Str::get_word(word)
+            .filter(|word| Str::valid_english(word))
+            .map(|word| word.to_owned())
+    }
+    /// Tokenizes in parallel, then keeps the first occurrence of every word
+    /// in input order, giving the same list as a sequential pass.
     pub fn lines(input: &str) -> Vec<String> {
-        let mut cache: HashSet<String> = HashSet::new();
+        let tokens: Vec<Option<String>> = Parse::sort_popular(input.split_whitespace().collect())
+            .par_iter()
+            .map(|word| Parse::token(word))
+            .collect();
+        let mut cache: HashSet<&str> = HashSet::new();
         let mut list = Vec::new();
-        for word in Parse::sort_popular(input.split_whitespace().collect()) {
-            let word = word.trim().to_lowercase();
-            let word = Str::rm_start_end(&word);
-            if let Some(word) = Str::get_word(word) {
-                let contain = cache.contains(word);
-                if !contain && Str::valid_english(word) {
-                    list.push(word.to_owned());
-                    cache.insert(word.to_owned());
-                }
+        for word in tokens.iter().flatten() {
+            if cache.insert(word) {
+                list.push(word.to_owned());
             }
         }
 
@@ -166,168 +109,332 @@ impl Parse {
     }
 }
 
-#[derive(Debug)]
-struct Writer {
-    path: String,
-    content: String,
-}
-impl Writer {
-    fn new(path: String, content: String) -> Self {
-        Self { path, content }
+struct Corpus;
+impl Corpus {
+    /// Keeps the words that pass the corpus thresholds, most widespread
+    /// first: by number of books, then by total count, then alphabetically.
+    fn rank(
+        list: &[String],
+        store: &HashMap<String, booktore::Frequency>,
+        min_books: usize,
+        min_count: usize,
+    ) -> Vec<String> {
+        let mut acc: Vec<_> = list
+            .iter()
+            .filter_map(|word| store.get(word).map(|frequency| (word, frequency)))
+            .filter(|(_, f)| f.books >= min_books && f.count >= min_count)
+            .collect();
+        acc.sort_by(|(word_a, a), (word_b, b)| {
+            b.books
+                .cmp(&a.books)
+                .then(b.count.cmp(&a.count))
+                .then(word_a.cmp(word_b))
+        });
+        acc.into_iter().map(|(word, _)| word.to_owned()).collect()
     }
 }
 
-#[derive(Debug)]
-struct Voc {
-    list: Vec<String>,
-    ing: Vec<String>,
-    ed: Vec<String>,
-    plural: Vec<String>,
-    simple: Vec<String>,
-    matching: Vec<String>,
-    writer: Vec<Option<Writer>>,
+/// Words of `word.on` that still need a decision, worked out without
+/// touching the file system. [`CandidateSet::output`] lays them out as the
+/// `word.on` and `parts/` files.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct CandidateSet {
+    /// Every word of `word.on` that is neither in the vocabulary nor in
+    /// `word.off`, in order.
+    pub list: Vec<String>,
+    /// The words of `list` kept by the corpus ranking, all of them when
+    /// `SORT_BY_CORPUS` is off.
+    pub candidates: Vec<String>,
+    /// Candidates accepted by `Str::is_match`, written to `match.on`.
+    pub matching: Vec<String>,
+    /// The other candidates by part file name, `F-5` holds the five letter
+    /// words.
+    pub parts: BTreeMap<String, Vec<String>>,
+    /// Candidates the corpus takes as proper nouns, in their corpus casing,
+    /// written to `proper.on` instead of a part.
+    pub proper: Vec<String>,
+    /// Words of the already processed prefix of `word.on` that the
+    /// vocabulary or `word.off` forbid since, taken out of `word.on` and the
+    /// part files.
+    pub pruned: Vec<String>,
+    /// How many words the already processed prefix holds, none on a full
+    /// run.
+    pub known: usize,
 }
-impl Voc {
-    fn new(list: Vec<String>) -> Self {
-        Self {
+
+impl CandidateSet {
+    fn new(list: Vec<String>, frequency: Option<&HashMap<String, booktore::Frequency>>) -> Self {
+        let candidates = match frequency {
+            Some(store) => Corpus::rank(&list, store, CORPUS_MIN_BOOKS, CORPUS_MIN_COUNT),
+            None => list.clone(),
+        };
+        let mut set = CandidateSet {
             list,
-            ing: vec![],
-            ed: vec![],
-            plural: vec![],
-            simple: vec![],
-            matching: vec![],
-            writer: Vec::new(),
+            ..Default::default()
+        };
+        for word in &candidates {
+            if Str::is_match(word) {
+                set.matching.push(word.to_owned())
+            } else {
+                if rule::is_min(word) || rule::is_max(word) {
+                    panic!("wrong length invalid data should't be at this point")
+                }
+                set.parts
+                    .entry(format!("F-{}", token::folder(word)))
+                    .or_default()
+                    .push(word.to_owned());
+            }
         }
+        set.candidates = candidates;
+        set
     }
 
-    fn write(name: &str, list: &Vec<String>, mas: &Mas) -> Option<Writer> {
-        let path = if name != "word.on" {
-            mas.path_parts.join(name)
-        } else {
-            mas.root.join(name)
-        };
-        let path = path.into_os_string().into_string().unwrap();
-        if list.len() > 0 || name == "word.on" {
-            let content = format!("{}\n", list.join("\n"));
-            let mi_writter = Writer::new(path, content);
-            return Some(mi_writter);
+    /// Moves the proper nouns of `matching` and `parts` to `proper`.
+    pub fn split_proper(&mut self, casing: &Casing) {
+        let mut proper = vec![];
+        for list in self.parts.values_mut().chain([&mut self.matching]) {
+            list.retain(|word| match casing.is_proper(word) {
+                true => {
+                    proper.push(casing.canonical(word));
+                    false
+                }
+                false => true,
+            });
         }
-
-        None
+        self.parts.retain(|_, list| !list.is_empty());
+        self.proper = proper;
     }
 
-    fn compose(&mut self, book_data: &HashMap<String, String>, mas: &Mas) -> &mut Self {
-        self.writer.push(Voc::write("word.on", &self.list, mas));
-        self.writer
-            .push(Voc::write("match.on", &self.matching, mas));
-
-        self.writer
-            .append(&mut Voc::insert(&self.ed, "N", book_data, mas));
-        self.writer
-            .append(&mut Voc::insert(&self.ing, "O", book_data, mas));
-        self.writer
-            .append(&mut Voc::insert(&self.plural, "P", book_data, mas));
-        self.writer
-            .append(&mut Voc::insert(&self.simple, "F", book_data, mas));
+    /// The lines of `content` whose word is not in `pruned`.
+    pub fn prune(&self, content: &str) -> String {
+        content
+            .lines()
+            .filter(|line| Parse::token(line).is_none_or(|word| !self.pruned.contains(&word)))
+            .map(|line| format!("{}\n", line))
+            .collect()
+    }
 
-        self
+    /// `word.on` and the part files, relative to the `mas` root. With the
+    /// `prefix` of an incremental run `word.on` keeps it, less the `pruned`
+    /// words, followed by the new words only, so lines appended again are
+    /// dropped; the part files are appended to.
+    pub fn output(
+        &self,
+        examples: &HashMap<String, (usize, String)>,
+        prefix: Option<&str>,
+    ) -> BuildOutput {
+        let mut output = BuildOutput::new();
+        let lines = |list: &[String]| format!("{}\n", list.join("\n"));
+        let mut add = |name: String, content: String| match prefix {
+            Some(_) => output.append(Path::new("parts").join(name), content),
+            None => output.push(Path::new("parts").join(name), content),
+        };
+        if !self.matching.is_empty() {
+            add("match.on".to_owned(), lines(&self.matching));
+        }
+        if !self.proper.is_empty() {
+            add("proper.on".to_owned(), lines(&self.proper));
+        }
+        for (name, list) in &self.parts {
+            if let Some(content) = booktore::compose(list, examples) {
+                add(format!("{}.off", name), content);
+            }
+            add(format!("{}.on", name), lines(list));
+        }
+        match prefix {
+            None => output.push("word.on", lines(&self.list)),
+            Some(prefix) if self.list.is_empty() => output.push("word.on", self.prune(prefix)),
+            Some(prefix) => output.push(
+                "word.on",
+                format!("{}{}", self.prune(prefix), lines(&self.list)),
+            ),
+        }
+        output
     }
-    fn direct_data(&mut self) -> &mut Self {
-        for word in self.list.clone() {
-            if Str::is_match(&word) {
-                self.matching.push(word)
-            } else {
-                self.simple.push(word)
+}
+
+/// Classifies `on_content` against the `vocabulary` and the words of
+/// `off_content`. Words of the already processed `prefix` of `word.on` are
+/// left out, or `pruned` when they are now forbidden, and `frequency`
+/// enables the corpus ranking.
+pub fn classify(
+    on_content: &str,
+    off_content: &str,
+    vocabulary: &[Word],
+    prefix: Option<&str>,
+    frequency: Option<&HashMap<String, booktore::Frequency>>,
+) -> CandidateSet {
+    let mut app = App::new(on_content.to_owned(), off_content.to_owned());
+    let mut pruned = vec![];
+    let mut total = 0;
+    let list = match prefix {
+        Some(prefix) => {
+            let known: HashSet<String> = Parse::lines(prefix).into_iter().collect();
+            total = known.len();
+            if let Some(rest) = app.on_content.strip_prefix(prefix) {
+                app.on_content = rest.to_owned();
             }
+            let list = app.start(vocabulary);
+            pruned = Parse::lines(prefix)
+                .into_iter()
+                .filter(|n| Word::get(n).is_some_and(|word| app.store.contains(&word)))
+                .collect();
+            list.into_iter().filter(|n| !known.contains(n)).collect()
         }
+        None => app.start(vocabulary),
+    };
+    let mut set = CandidateSet::new(list, frequency);
+    set.pruned = pruned;
+    set.known = total;
+    set
+}
 
-        self
+/// What to do when `word.off` lists words that are not in `word.on`,
+/// chosen with `--on-missing=`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Missing {
+    /// Stop before touching any file.
+    Fail,
+    /// Report and go on.
+    Continue,
+    /// Add the missing words at the end of `word.on`.
+    Append,
+    /// Take the missing words out of `word.off`.
+    Drop,
+}
+
+impl Default for Missing {
+    fn default() -> Self {
+        ON_MISSING
+    }
+}
+
+impl FromStr for Missing {
+    type Err = String;
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        match input {
+            "fail" => Ok(Missing::Fail),
+            "continue" => Ok(Missing::Continue),
+            "append" => Ok(Missing::Append),
+            "drop" => Ok(Missing::Drop),
+            _ => Err(format!(
+                "({}) is not an on-missing policy, use fail, continue, append or drop",
+                input
+            )),
+        }
     }
-    fn collect(
-        store: &mut HashMap<usize, Vec<String>>,
-        letter: &str,
-        book_data: &HashMap<String, String>,
-        mas: &Mas,
-    ) -> Vec<Option<Writer>> {
-        let mut inner = vec![];
-        for rank in store.keys() {
-            let list = store.get(rank).unwrap();
-            let name = format!("{}-{}.off", letter, rank);
-            let name = mas
-                .path_parts
-                .join(name)
-                .into_os_string()
-                .into_string()
-                .unwrap();
-            booktore::write_to_file(Path::new(&name), list, book_data);
-            inner.push(Voc::write(&format!("{}-{}.on", letter, rank), list, mas));
-        }
-        inner
-    }
-
-    fn insert(
-        list: &Vec<String>,
-        letter: &str,
-        book_data: &HashMap<String, String>,
-        mas: &Mas,
-    ) -> Vec<Option<Writer>> {
-        let mut store = Voc::store();
-
-        for word in list {
-            if let Some(node) = store.get_mut(&word.len()) {
-                node.push(word.to_owned());
+}
+
+/// Agreement between `word.on` and `word.off`.
+#[derive(Debug, Default, PartialEq)]
+pub struct Consistency {
+    /// Off words that `word.on` does not contain.
+    pub missing: Vec<String>,
+    /// Off words that `word.on` contains, these are the ones being filtered.
+    pub in_both: Vec<String>,
+    /// Spellings of both files that only differ by case or accents.
+    pub near_duplicates: Vec<Vec<String>>,
+}
+
+impl Consistency {
+    fn check(on_content: &str, off_list: &[String]) -> Self {
+        let raw: Vec<&str> = on_content
+            .split_whitespace()
+            .map(|word| word.trim_matches(|letter: char| !letter.is_alphabetic()))
+            .filter(|word| !word.is_empty())
+            .collect();
+        let on_words: HashSet<String> = raw.iter().filter_map(|w| token::word(w)).collect();
+
+        let mut consistency = Consistency::default();
+        for word in off_list {
+            if on_words.contains(word) {
+                consistency.in_both.push(word.to_owned());
             } else {
-                panic!("wrong length invalid data should't be at this point")
+                consistency.missing.push(word.to_owned());
             }
         }
-        Voc::collect(&mut store, letter, book_data, mas)
+
+        let spellings = raw
+            .iter()
+            .map(|word| normal::nfc(word))
+            .chain(off_list.iter().cloned());
+        consistency.near_duplicates = normal::near_duplicates(spellings);
+        consistency
+    }
+
+    pub fn is_consistent(&self) -> bool {
+        self.missing.is_empty()
     }
-    fn store() -> HashMap<usize, Vec<String>> {
-        let mut store = HashMap::new();
-        let mut start = rule::Word::min();
-        while start <= rule::Word::max() {
-            store.insert(start, vec![]);
-            start += 1;
+
+    pub fn report(&self) -> String {
+        let mut acc = String::new();
+        for word in &self.missing {
+            acc.push_str(&format!("({}) does not exist! in word.on\n", word));
         }
-        store
+        for group in &self.near_duplicates {
+            acc.push_str(&format!(
+                "({}) only differ by case or accents\n",
+                group.join(", ")
+            ));
+        }
+        acc.push_str(&format!(
+            "word.off: {} missing from word.on, {} in both, {} near duplicates\n",
+            self.missing.len(),
+            self.in_both.len(),
+            self.near_duplicates.len()
+        ));
+        acc
     }
-    fn write_to_files(&mut self) {
-        for writer in &self.writer {
-            if let Some(write) = writer {
-                fs::write(&write.path, &write.content).unwrap();
-            }
+}
+
+#[derive(Debug)]
+pub enum Error {
+    Inconsistent(Consistency),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Inconsistent(consistency) => write!(
+                f,
+                "word.off lists {} words missing from word.on",
+                consistency.missing.len()
+            ),
         }
-        eprintln!("Mas: {}", self.list.len());
     }
 }
 
+impl std::error::Error for Error {}
+
 struct App {
     off_content: String,
     on_content: String,
-    store: HashSet<String>,
+    store: Tipo,
 }
-fn update_off_file(list: Vec<String>, mas: &Mas) {
-    let mut acc = vec![];
-    let mut store = HashSet::new();
-    for word in list {
-        store.insert(word);
+fn update_off_file(list: Vec<Word>, mas: &Mas) {
+    let store: HashSet<Word> = list.into_iter().collect();
+    let mut off = OffFile::read(&mas.path_off);
+    let cleanup = off.cleanup(&store);
+    if cleanup.is_empty() {
+        return;
     }
-
-    for word in Parse::lines(&fs::read_to_string(&mas.path_off).unwrap()) {
-        if !store.contains(&word) {
-            acc.push(word);
-        }
+    eprint!("{}:\n{}", mas.path_off.display(), cleanup.diff());
+    if UPDATE_OFF_FILE {
+        off.write();
     }
-    acc.sort_by(|a, b| a.len().cmp(&b.len()));
-    fs::write(&mas.path_off, acc.join("\n")).unwrap();
 }
 
 struct Forbid;
 impl Forbid {
-    fn start(mut store: Tipo, listas: Vec<&Vec<String>>) -> Tipo {
+    /// Proper nouns are kept capitalized, they forbid their lowercase
+    /// spelling too since that is what `Parse::lines` gives.
+    fn start(mut store: Tipo, listas: Vec<&[Word]>) -> Tipo {
         for lista in listas {
             for line in lista {
-                store.insert(line.to_owned());
+                store.insert(*line);
+                if line.chars().any(char::is_uppercase) {
+                    store.insert(Word::new(&line.to_lowercase()));
+                }
             }
         }
         store
@@ -341,38 +448,74 @@ impl App {
             store: HashSet::new(),
         }
     }
-    fn forbid(&mut self, vocabulary_list: &Vec<String>, off_list: &Vec<String>) -> &mut Self {
+    fn forbid(&mut self, vocabulary_list: &[Word], off_list: &[Word]) -> &mut Self {
         let store: Tipo = HashSet::new();
         self.store = Forbid::start(store, vec![vocabulary_list, off_list]);
         self
     }
-    fn start(&mut self, vocabulary_list: &Vec<String>) -> Vec<String> {
-        let off_list = Parse::lines(&self.off_content);
+    fn off_list(&self) -> Vec<String> {
+        OffFile::new("")
+            .parse(&self.off_content)
+            .words()
+            .iter()
+            .map(|word| word.to_string())
+            .collect()
+    }
+    fn consistency(&self) -> Consistency {
+        Consistency::check(&self.on_content, &self.off_list())
+    }
+    fn start(&mut self, vocabulary_list: &[Word]) -> Vec<String> {
+        let off_list = self.off_list();
+        let off_list = Word::list(off_list.iter().map(|word| word.as_str()));
         self.forbid(vocabulary_list, &off_list);
 
-        let data = Parse::lines(&self.on_content).into_iter();
-        if MUST_CONTAINS_WORDS {
-            let mut errors = vec![];
-            let on_file_data = data.clone().fold(HashSet::new(), |mut acc, word| {
-                acc.insert(word);
-                acc
-            });
+        Parse::lines(&self.on_content)
+            .into_iter()
+            .filter(|n| Word::get(n).is_none_or(|word| !self.store.contains(&word)))
+            .collect()
+    }
+}
 
-            for word in off_list {
-                if !on_file_data.contains(&word) {
-                    errors.push(word);
-                }
-            }
+/// How much of `word.on` the last run already classified.
+#[derive(Debug, PartialEq)]
+struct Checkpoint {
+    offset: usize,
+    hash: String,
+}
 
-            for word in &errors {
-                println!("({}) does not exist! in word.on", &word);
-            }
-            if errors.len() > 0 {
-                panic!("contains duplicate words in total: {}", errors.len());
+impl Checkpoint {
+    fn new(content: &str) -> Self {
+        Self {
+            offset: content.len(),
+            hash: checksum::hex(content.as_bytes()),
+        }
+    }
+
+    fn parse(content: &str) -> Option<Self> {
+        let mut offset = None;
+        let mut hash = None;
+        for line in content.lines() {
+            match line.split_once(':') {
+                Some(("offset", value)) => offset = value.trim().parse().ok(),
+                Some(("hash", value)) => hash = Some(value.trim().to_owned()),
+                _ => {}
             }
         }
+        Some(Self {
+            offset: offset?,
+            hash: hash?,
+        })
+    }
+
+    fn content(&self) -> String {
+        format!("offset: {}\nhash: {}\n", self.offset, self.hash)
+    }
 
-        data.filter(|n| !self.store.contains(n)).collect()
+    /// The already processed start of `content`, if it is unchanged since
+    /// the checkpoint was taken.
+    fn prefix<'a>(&self, content: &'a str) -> Option<&'a str> {
+        let prefix = content.get(..self.offset)?;
+        (checksum::hex(prefix.as_bytes()) == self.hash).then_some(prefix)
     }
 }
 
@@ -382,6 +525,15 @@ pub struct Mas {
     path_on: PathBuf,
     path_off: PathBuf,
     path_parts: PathBuf,
+    path_checkpoint: PathBuf,
+    full: bool,
+    on_missing: Missing,
+}
+
+impl Default for Mas {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl Mas {
@@ -391,25 +543,31 @@ impl Mas {
             path_on: PathBuf::new(),
             path_off: PathBuf::new(),
             path_parts: PathBuf::new(),
+            path_checkpoint: PathBuf::new(),
+            full: false,
+            on_missing: Missing::default(),
         }
     }
 
-    fn get_path(self, name: &str) -> PathBuf {
+    fn get_path(&self, name: &str) -> PathBuf {
         if name == "aparter" {
             PathBuf::from(name)
         } else {
-            self.root
+            self.root.to_owned()
         }
     }
-    fn get_path_on(self) -> PathBuf {
+    fn get_path_on(&self) -> PathBuf {
         self.root.join("word.on")
     }
-    fn get_path_off(self) -> PathBuf {
+    fn get_path_off(&self) -> PathBuf {
         self.root.join("word.off")
     }
-    fn get_path_parts(self) -> PathBuf {
+    fn get_path_parts(&self) -> PathBuf {
         self.root.join("parts")
     }
+    fn get_path_checkpoint(&self) -> PathBuf {
+        self.root.join("word.checkpoint")
+    }
 
     pub fn setup(mut self, name: &str) -> Self {
         if name == "aparter" {
@@ -417,56 +575,352 @@ impl Mas {
         } else {
             println!("\nNORMAL Running...");
         }
-        self.root = self.clone().get_path(name);
-        self.path_on = self.clone().get_path_on();
-        self.path_off = self.clone().get_path_off();
-        self.path_parts = self.clone().get_path_parts();
+        self.root = self.get_path(name);
+        self.path_on = self.get_path_on();
+        self.path_off = self.get_path_off();
+        self.path_parts = self.get_path_parts();
+        self.path_checkpoint = self.get_path_checkpoint();
         self
     }
-    pub fn start(self, not_allow: Vec<String>) -> Vec<String> {
-        let book_data = if USING_BOOKTORE {
-            booktore::init()
-        } else {
-            HashMap::new()
-        };
+    /// Reclassifies the whole `word.on` and regenerates every part file
+    /// instead of only processing the lines added since the last run.
+    pub fn full(mut self, full: bool) -> Self {
+        self.full = full;
+        self
+    }
+    pub fn on_missing(mut self, on_missing: Missing) -> Self {
+        self.on_missing = on_missing;
+        self
+    }
+    fn checkpoint(&self, on_content: &str) -> Option<String> {
+        if self.full || !self.path_parts.is_dir() {
+            return None;
+        }
+        let content = fs::read_to_string(&self.path_checkpoint).ok()?;
+        let prefix = Checkpoint::parse(&content)?.prefix(on_content)?;
+        Some(prefix.to_owned())
+    }
+    /// Makes `word.on` and `word.off` agree according to `on_missing`,
+    /// failing before any file is touched when it is `Missing::Fail`.
+    fn check(&self, app: &mut App) -> Result<(), Error> {
+        if !MUST_CONTAINS_WORDS {
+            return Ok(());
+        }
+        let consistency = app.consistency();
+        eprint!("{}", consistency.report());
+        if consistency.is_consistent() {
+            return Ok(());
+        }
+        match self.on_missing {
+            Missing::Fail => return Err(Error::Inconsistent(consistency)),
+            Missing::Continue => {}
+            Missing::Append => {
+                if !app.on_content.is_empty() && !app.on_content.ends_with('\n') {
+                    app.on_content.push('\n');
+                }
+                app.on_content
+                    .push_str(&format!("{}\n", consistency.missing.join("\n")));
+                fs::write(&self.path_on, &app.on_content).unwrap();
+                eprintln!("{} words appended to word.on", consistency.missing.len());
+            }
+            Missing::Drop => {
+                let mut off = OffFile::read(&self.path_off);
+                let total = off.remove(&consistency.missing.into_iter().collect());
+                off.write();
+                app.off_content = off.content();
+                eprintln!("{} words dropped from word.off", total);
+            }
+        }
+        Ok(())
+    }
+    /// Takes the `pruned` words out of the part files of previous runs.
+    fn prune_parts(&self, set: &CandidateSet) {
+        for entry in fs::read_dir(&self.path_parts).into_iter().flatten() {
+            let path = entry.unwrap().path();
+            if path.extension().is_none_or(|extension| extension != "on") {
+                continue;
+            }
+            let content = fs::read_to_string(&path).unwrap();
+            let pruned = set.prune(&content);
+            if pruned != content {
+                fs::write(&path, pruned).unwrap();
+            }
+        }
+    }
+    pub fn path_off(&self) -> &Path {
+        &self.path_off
+    }
+
+    /// Classifies the whole `word.on` without writing anything.
+    pub fn candidates(&self, not_allow: &[Word]) -> CandidateSet {
+        let read = |path: &Path| normal::nfc(&fs::read_to_string(path).unwrap_or_default());
+        let frequency = SORT_BY_CORPUS.then(booktore::init_frequency);
+        classify(
+            &read(&self.path_on),
+            &read(&self.path_off),
+            not_allow,
+            None,
+            frequency.as_ref(),
+        )
+    }
+
+    /// Runs `mas`, journaling every file it changes.
+    pub fn start(self, not_allow: Vec<Word>) -> Result<Vec<String>, Error> {
+        let paths = [
+            self.path_on.as_path(),
+            self.path_off.as_path(),
+            self.path_parts.as_path(),
+            self.path_checkpoint.as_path(),
+        ];
+        journal::journal().record("mas", &paths, || self.run(not_allow))
+    }
 
-        for inner_path in vec![&self.path_on, &self.path_off] {
+    fn run(&self, not_allow: Vec<Word>) -> Result<Vec<String>, Error> {
+        for inner_path in [&self.path_on, &self.path_off] {
             if !inner_path.exists() {
                 fs::File::create(inner_path).unwrap();
             }
         }
-        if self.path_parts.is_dir() {
-            fs::remove_dir_all(&self.path_parts).unwrap();
-        }
-        let on_content = fs::read_to_string(&self.path_on).unwrap();
-        let off_content = fs::read_to_string(&self.path_off).unwrap();
+        let on_content = normal::nfc(&fs::read_to_string(&self.path_on).unwrap());
+        let off_content = normal::nfc(&fs::read_to_string(&self.path_off).unwrap());
+        let mut app = App::new(on_content, off_content);
+        self.check(&mut app)?;
 
-        fs::create_dir(&self.path_parts).unwrap();
-        let list = App::new(on_content, off_content).start(&not_allow);
-        Voc::new(list.clone())
-            .direct_data()
-            .compose(&book_data, &self)
-            .write_to_files();
-        update_off_file(not_allow, &self);
-        list
+        let prefix = self.checkpoint(&app.on_content);
+        if prefix.is_none() {
+            output::clean(&self.path_parts);
+        }
+        let frequency = SORT_BY_CORPUS.then(booktore::init_frequency);
+        let mut set = classify(
+            &app.on_content,
+            &app.off_content,
+            &not_allow,
+            prefix.as_deref(),
+            frequency.as_ref(),
+        );
+        if TRUECASE {
+            set.split_proper(&booktore::init_casing());
+        }
+        let examples = if USING_BOOKTORE {
+            booktore::examples_of(&set.candidates)
+        } else {
+            ExampleStore::default()
+        };
+        if prefix.is_some() {
+            eprintln!("Incremental: {} words already processed", set.known);
+        }
+        if prefix.is_some() && !set.pruned.is_empty() {
+            self.prune_parts(&set);
+            eprintln!("Pruned: {}", set.pruned.join(", "));
+        }
+        set.output(&examples.words, prefix.as_deref())
+            .write(&self.root);
+        eprintln!("Mas: {}", set.list.len());
+        if SORT_BY_CORPUS {
+            eprintln!("Corpus candidates: {}", set.candidates.len());
+        }
+        let on_content = normal::nfc(&fs::read_to_string(&self.path_on).unwrap());
+        fs::write(
+            &self.path_checkpoint,
+            Checkpoint::new(&on_content).content(),
+        )
+        .unwrap();
+        update_off_file(not_allow, self);
+        Ok(set.candidates)
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use rule::Word;
+    use proptest::prelude::*;
+    use rule::Length;
     #[test]
-    fn store_test() {
-        let store = Voc::store();
-        for rank in Word::min()..Word::max() {
-            assert_eq!(store.get(&rank), Some(&vec![]));
+    fn popular_test() {
+        let list = Parse::popular(vec!["c", "b", "a", "d", "a", "b"]);
+        assert_eq!(list, vec!["a", "b", "c", "d"]);
+    }
+    #[test]
+    fn corpus_rank_test() {
+        use booktore::Frequency;
+        let store: HashMap<_, _> = [
+            (
+                "rare",
+                Frequency {
+                    count: 90,
+                    books: 1,
+                },
+            ),
+            (
+                "casa",
+                Frequency {
+                    count: 10,
+                    books: 4,
+                },
+            ),
+            (
+                "perro",
+                Frequency {
+                    count: 20,
+                    books: 4,
+                },
+            ),
+            (
+                "gato",
+                Frequency {
+                    count: 10,
+                    books: 4,
+                },
+            ),
+        ]
+        .into_iter()
+        .map(|(word, frequency)| (word.to_owned(), frequency))
+        .collect();
+        let list: Vec<String> = ["rare", "gato", "casa", "perro", "nada"]
+            .iter()
+            .map(|w| w.to_string())
+            .collect();
+        assert_eq!(
+            Corpus::rank(&list, &store, 3, 1),
+            vec!["perro", "casa", "gato"]
+        );
+        assert_eq!(Corpus::rank(&list, &store, 1, 50), vec!["rare"]);
+    }
+    #[test]
+    fn consistency_test() {
+        let off: Vec<String> = ["casa", "perro", "esta"]
+            .iter()
+            .map(|w| w.to_string())
+            .collect();
+        let consistency = Consistency::check("casa\ngato\nEstá\nGato\n", &off);
+        assert_eq!(consistency.missing, vec!["perro", "esta"]);
+        assert_eq!(consistency.in_both, vec!["casa"]);
+        assert_eq!(
+            consistency.near_duplicates,
+            vec![vec!["Está", "esta"], vec!["Gato", "gato"]]
+        );
+        assert!(!consistency.is_consistent());
+        assert!(Consistency::check("casa", &off[..1]).is_consistent());
+    }
+    #[test]
+    fn missing_test() {
+        assert_eq!("drop".parse(), Ok(Missing::Drop));
+        assert_eq!("append".parse(), Ok(Missing::Append));
+        assert_eq!(Missing::default(), ON_MISSING);
+        assert!("keep".parse::<Missing>().is_err());
+    }
+    #[test]
+    fn lines_test() {
+        let input = "Casa, perro\ncasa\tá a gato.\n\nPERRO working";
+        assert_eq!(
+            Parse::lines(input),
+            vec!["casa", "perro", "gato", "working"]
+        );
+    }
+    #[test]
+    fn checkpoint_test() {
+        let checkpoint = Checkpoint::new("casa\nperro\n");
+        assert_eq!(checkpoint.offset, 11);
+        assert_eq!(Checkpoint::parse(&checkpoint.content()), Some(checkpoint));
+        let checkpoint = Checkpoint::new("casa\nperro\n");
+        assert_eq!(
+            checkpoint.prefix("casa\nperro\ngato\n"),
+            Some("casa\nperro\n")
+        );
+        assert_eq!(checkpoint.prefix("cosa\nperro\ngato\n"), None);
+        assert_eq!(checkpoint.prefix("casa\n"), None);
+        assert_eq!(Checkpoint::parse("offset: 3\n"), None);
+    }
+    #[test]
+    fn classify_test() {
+        let vocabulary = [Word::new("casa")];
+        let set = classify("casa perro gato\nlibro", "gato\n", &vocabulary, None, None);
+        assert_eq!(set.list, vec!["perro", "libro"]);
+        assert_eq!(set.candidates, set.list);
+        assert_eq!(set.parts["F-5"], vec!["perro", "libro"]);
+
+        let output = set.output(&HashMap::new(), None);
+        assert_eq!(output.get("word.on"), Some("perro\nlibro\n"));
+        assert_eq!(output.get("parts/F-5.on"), Some("perro\nlibro\n"));
+        assert_eq!(output.get("parts/F-5.off"), None);
+
+        let output = set.output(&HashMap::new(), Some("casa\n"));
+        assert_eq!(output.get("word.on"), Some("casa\nperro\nlibro\n"));
+        assert!(output.files[0].append);
+
+        let set = classify(
+            "casa\nmesa\nlibro",
+            "",
+            &vocabulary,
+            Some("casa\nmesa\n"),
+            None,
+        );
+        assert_eq!(set.pruned, vec!["casa"]);
+        assert_eq!(set.known, 2);
+        assert_eq!(set.list, vec!["libro"]);
+        let output = set.output(&HashMap::new(), Some("casa\nmesa\n"));
+        assert_eq!(output.get("word.on"), Some("mesa\nlibro\n"));
+    }
+    #[test]
+    fn incremental_prune_test() {
+        let dir = std::env::temp_dir().join(format!("gamo-mas-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let mas = Mas {
+            root: dir.to_owned(),
+            ..Mas::new()
         }
+        .setup("test");
+        fs::write(&mas.path_on, "mesa\nperro\n").unwrap();
+        mas.run(vec![]).unwrap();
+        assert_eq!(
+            fs::read_to_string(mas.path_parts.join("F-5.on")).unwrap(),
+            "perro\n"
+        );
+
+        let mut on = fs::read_to_string(&mas.path_on).unwrap();
+        on.push_str("libro\n");
+        fs::write(&mas.path_on, on).unwrap();
+        let vocabulary = vec![Word::new("perro")];
+        mas.run(vocabulary).unwrap();
+        let read = |path: PathBuf| fs::read_to_string(path).unwrap();
+        let on = read(mas.path_on.to_owned());
+        let parts = read(mas.path_parts.join("F-5.on"));
+        let mesa = read(mas.path_parts.join("F-4.on"));
+        fs::write(&mas.path_on, format!("{}mesa\nlibro\n", on)).unwrap();
+        mas.run(vec![Word::new("perro")]).unwrap();
+        let again = read(mas.path_on.to_owned());
+        fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(on, "mesa\nlibro\n");
+        assert_eq!(again, on);
+        assert_eq!(parts, "libro\n");
+        assert_eq!(mesa, "mesa\n");
+    }
+    #[test]
+    fn split_proper_test() {
+        let vocabulary = [Word::new("Paris")];
+        let set = classify(
+            "paris musk house
+london",
+            "",
+            &vocabulary,
+            None,
+            None,
+        );
+        assert_eq!(set.list, vec!["musk", "house", "london"]);
+        let text = "to London. I saw Musk and Musk, then Musk, at London and London.";
+        let mut set = set;
+        set.split_proper(&Casing::new(text.split_whitespace()));
+        assert_eq!(set.proper, vec!["Musk", "London"]);
+        assert_eq!(set.parts["F-5"], vec!["house"]);
+        assert_eq!(set.parts.len(), 1);
+        let output = set.output(&HashMap::new(), None);
+        assert_eq!(output.get("parts/proper.on"), Some("Musk\nLondon\n"));
     }
     #[test]
     fn get_word_test() {
-        let max_letter = "a".repeat(Word::max() + 1);
-        let min_letter = "a".repeat(Word::min() - 1);
+        let max_letter = "a".repeat(Length::max() + 1);
+        let min_letter = "a".repeat(Length::min() - 1);
         assert!(Str::get_word(&max_letter).is_none());
         assert!(Str::get_word(&min_letter).is_none());
         assert_eq!(Str::get_word("hello"), Some("hello"));
@@ -487,29 +941,35 @@ mod tests {
         assert!(Str::is_ing("working"));
         assert!(!Str::is_ing("worknng"));
         assert!(!Str::is_ing(""));
-    }
-    #[test]
-    fn valid_english_test() {
-        assert!(Str::valid_english("z"));
-        assert!(Str::valid_english("ab"));
-        assert!(!Str::valid_english(""));
-    }
-    #[test]
-    fn rm_start_end_test() {
-        assert_eq!(Str::rm_start_end("  "), "");
-        assert_eq!(Str::rm_start_end(" hello "), "hello");
-        assert_eq!(Str::rm_start_end("1/#*hello1/#*"), "hello");
-    }
-    #[test]
-    fn rm_start_test() {
-        assert_eq!(Str::rm_start("  "), "");
-        assert_eq!(Str::rm_start(" hello"), "hello");
-        assert_eq!(Str::rm_start("1/#*hello"), "hello");
-    }
-    #[test]
-    fn rm_end_test() {
-        assert_eq!(Str::rm_end("  "), "");
-        assert_eq!(Str::rm_end("hello "), "hello");
-        assert_eq!(Str::rm_end("hello1/#*"), "hello");
+        assert!(!Str::is_ing("ñé"));
+        assert!(!Str::is_ing("canción"));
+    }
+    proptest! {
+        #[test]
+        fn str_prop(input in "\\PC*") {
+            Str::is_ing(&input);
+            Str::is_ed(&input);
+            Str::is_plural(&input);
+            Str::is_match(&input);
+        }
+        #[test]
+        fn lines_prop(input in "\\PC*") {
+            let list = Parse::lines(&input);
+            let unique: HashSet<_> = list.iter().collect();
+            prop_assert_eq!(unique.len(), list.len());
+            for word in &list {
+                prop_assert!(Str::valid_english(word));
+                prop_assert_eq!(&Parse::token(word), &Some(word.to_owned()));
+            }
+        }
+        #[test]
+        fn classify_prop(on in "\\PC*", off in "\\PC*", split in 0..64usize) {
+            let vocabulary = Word::list(off.split_whitespace().take(3));
+            let prefix = on.get(..split);
+            let set = classify(&on, &off, &vocabulary, prefix, None);
+            for word in &set.list {
+                prop_assert!(Str::valid_english(word));
+            }
+        }
     }
 }
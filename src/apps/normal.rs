@@ -0,0 +1,92 @@
+use std::collections::HashMap;
+use unicode_normalization::{
+    char::is_combining_mark, is_nfc_quick, IsNormalized, UnicodeNormalization,
+};
+
+/// Composed form, "cancio\u{301}n" becomes "canción". Text that is already
+/// composed, most of it, is only copied.
+pub fn nfc(input: &str) -> String {
+    match is_nfc_quick(input.chars()) {
+        IsNormalized::Yes => input.to_owned(),
+        _ => input.nfc().collect(),
+    }
+}
+
+pub fn word(input: &str) -> String {
+    nfc(&input.trim().to_lowercase())
+}
+
+/// Comparison key that ignores case and accents, "Está" and "esta" fold to
+/// the same key.
+pub fn fold(input: &str) -> String {
+    input
+        .nfd()
+        .filter(|letter| !is_combining_mark(*letter))
+        .collect::<String>()
+        .to_lowercase()
+}
+
+/// Groups of distinct spellings that share a [`fold`] key, like "esta" and
+/// "está". Each group and the list are sorted.
+pub fn near_duplicates<T: IntoIterator<Item = String>>(words: T) -> Vec<Vec<String>> {
+    let mut groups: HashMap<String, Vec<String>> = HashMap::new();
+    for word in words {
+        let group = groups.entry(fold(&word)).or_default();
+        if !group.contains(&word) {
+            group.push(word);
+        }
+    }
+    let mut acc: Vec<Vec<String>> = groups
+        .into_values()
+        .filter(|group| group.len() > 1)
+        .map(|mut group| {
+            group.sort();
+            group
+        })
+        .collect();
+    acc.sort();
+    acc
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+    #[test]
+    fn nfc_test() {
+        let decomposed = "cancio\u{301}n";
+        assert_ne!(decomposed, "canción");
+        assert_eq!(nfc(decomposed), "canción");
+    }
+    #[test]
+    fn fold_test() {
+        assert_eq!(fold("Está"), "esta");
+        assert_eq!(fold("cancio\u{301}n"), fold("CANCIÓN"));
+        assert_eq!(fold("niño"), "nino");
+    }
+    #[test]
+    fn near_duplicates_test() {
+        let words = ["esta", "está", "casa", "esta", "Casa", "perro"];
+        assert_eq!(
+            near_duplicates(words.iter().map(|w| w.to_string())),
+            vec![vec!["Casa", "casa"], vec!["esta", "está"]]
+        );
+    }
+    #[test]
+    fn word_test() {
+        assert_eq!(word("  España "), "españa");
+        assert_eq!(word("CANCIO\u{301}N"), "canción");
+    }
+    proptest! {
+        #[test]
+        fn fold_prop(input in "\\PC*") {
+            let folded = fold(&input);
+            prop_assert_eq!(fold(&folded), folded);
+        }
+        #[test]
+        fn nfc_prop(input in "\\PC*") {
+            let composed = nfc(&input);
+            prop_assert_eq!(nfc(&composed), composed);
+        }
+    }
+}
@@ -0,0 +1,302 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fmt, fs,
+    path::{Path, PathBuf},
+};
+
+use crate::apps::{intern::Word, normal};
+
+const COMMENT: &str = "//";
+
+/// One line of `word.off`. Besides rejected words the file may hold `//`
+/// comments, `[section]` headers and blank lines, and every word may carry a
+/// `// reason` explaining why it was rejected. Words are compared by their
+/// normalized form but written back as `spelling`, the way they were typed.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Line {
+    Blank,
+    Comment(String),
+    Section(String),
+    Word {
+        word: String,
+        spelling: String,
+        reason: Option<String>,
+    },
+}
+
+impl Line {
+    fn parse(line: &str) -> Self {
+        let line = line.trim();
+        if line.is_empty() {
+            return Line::Blank;
+        }
+        if line.starts_with(COMMENT) {
+            return Line::Comment(line.to_owned());
+        }
+        if line.starts_with('[') && line.ends_with(']') {
+            return Line::Section(line[1..line.len() - 1].trim().to_owned());
+        }
+        match line.split_once(COMMENT) {
+            Some((word, reason)) => Line::Word {
+                word: normal::word(word),
+                spelling: word.trim().to_owned(),
+                reason: Some(reason.trim().to_owned()).filter(|n| !n.is_empty()),
+            },
+            None => Line::Word {
+                word: normal::word(line),
+                spelling: line.to_owned(),
+                reason: None,
+            },
+        }
+    }
+}
+
+impl fmt::Display for Line {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Line::Blank => Ok(()),
+            Line::Comment(comment) => write!(f, "{}", comment),
+            Line::Section(name) => write!(f, "[{}]", name),
+            Line::Word {
+                spelling,
+                reason: None,
+                ..
+            } => write!(f, "{}", spelling),
+            Line::Word {
+                spelling,
+                reason: Some(reason),
+                ..
+            } => write!(f, "{} {} {}", spelling, COMMENT, reason),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Removed {
+    pub line: usize,
+    pub word: String,
+    pub why: String,
+}
+
+/// What a cleanup took out of `word.off`, line numbers refer to the file
+/// before the cleanup.
+#[derive(Debug, Default, PartialEq)]
+pub struct Cleanup {
+    pub removed: Vec<Removed>,
+}
+
+impl Cleanup {
+    pub fn is_empty(&self) -> bool {
+        self.removed.is_empty()
+    }
+
+    pub fn diff(&self) -> String {
+        self.removed.iter().fold(String::new(), |mut acc, removed| {
+            acc.push_str(&format!(
+                "-{}: {} ({})\n",
+                removed.line, removed.word, removed.why
+            ));
+            acc
+        })
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct OffFile {
+    pub path: PathBuf,
+    pub lines: Vec<Line>,
+}
+
+impl OffFile {
+    pub fn new<T: AsRef<Path>>(path: T) -> Self {
+        Self {
+            path: path.as_ref().to_path_buf(),
+            lines: Vec::new(),
+        }
+    }
+
+    pub fn read<T: AsRef<Path>>(path: T) -> Self {
+        let content = fs::read_to_string(&path).unwrap_or_default();
+        OffFile::new(path).parse(&content)
+    }
+
+    pub fn parse(mut self, content: &str) -> Self {
+        self.lines = content.lines().map(Line::parse).collect();
+        while self.lines.last() == Some(&Line::Blank) {
+            self.lines.pop();
+        }
+        self
+    }
+
+    pub fn words(&self) -> Vec<&str> {
+        self.lines
+            .iter()
+            .filter_map(|line| match line {
+                Line::Word { word, .. } => Some(word.as_str()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    pub fn reason(&self, word: &str) -> Option<&str> {
+        self.lines.iter().find_map(|line| match line {
+            Line::Word {
+                word: w, reason, ..
+            } if w == word => reason.as_deref(),
+            _ => None,
+        })
+    }
+
+    /// Appends `word` at the end of `section`, creating the section when it
+    /// does not exist, or at the end of the file. Returns false when the word
+    /// was already rejected.
+    pub fn reject(&mut self, word: &str, reason: Option<&str>, section: Option<&str>) -> bool {
+        let word = normal::word(word);
+        if word.is_empty() || self.words().contains(&word.as_str()) {
+            return false;
+        }
+        let line = Line::Word {
+            spelling: word.to_owned(),
+            word,
+            reason: reason.map(|n| n.to_owned()),
+        };
+        let section = match section {
+            Some(section) => section,
+            None => {
+                self.lines.push(line);
+                return true;
+            }
+        };
+        let start = self
+            .lines
+            .iter()
+            .position(|n| matches!(n, Line::Section(name) if name == section));
+        match start {
+            Some(start) => {
+                let end = self.lines[start + 1..]
+                    .iter()
+                    .position(|n| matches!(n, Line::Section(_)))
+                    .map_or(self.lines.len(), |n| start + 1 + n);
+                let mut at = end;
+                while at > start + 1 && self.lines[at - 1] == Line::Blank {
+                    at -= 1;
+                }
+                self.lines.insert(at, line);
+            }
+            None => {
+                if !self.lines.is_empty() {
+                    self.lines.push(Line::Blank);
+                }
+                self.lines.push(Line::Section(section.to_owned()));
+                self.lines.push(line);
+            }
+        }
+        true
+    }
+
+    /// Takes every line holding one of `words` out of the file and returns
+    /// how many were removed.
+    pub fn remove(&mut self, words: &HashSet<String>) -> usize {
+        let before = self.lines.len();
+        self.lines
+            .retain(|line| !matches!(line, Line::Word { word, .. } if words.contains(word)));
+        before - self.lines.len()
+    }
+
+    /// Drops words that made it into the vocabulary and repeated words,
+    /// keeping comments, sections and the order chosen by the editor.
+    pub fn cleanup(&mut self, vocabulary: &HashSet<Word>) -> Cleanup {
+        let mut cleanup = Cleanup::default();
+        let mut seen: HashMap<String, usize> = HashMap::new();
+        let mut lines = Vec::with_capacity(self.lines.len());
+        for (index, line) in self.lines.drain(..).enumerate() {
+            if let Line::Word { word, .. } = &line {
+                let why = if Word::get(word).is_some_and(|w| vocabulary.contains(&w)) {
+                    Some("now in vocabulary".to_owned())
+                } else {
+                    seen.get(word)
+                        .map(|first| format!("repeats line {}", first))
+                };
+                if let Some(why) = why {
+                    cleanup.removed.push(Removed {
+                        line: index + 1,
+                        word: word.to_owned(),
+                        why,
+                    });
+                    continue;
+                }
+                seen.insert(word.to_owned(), index + 1);
+            }
+            lines.push(line);
+        }
+        self.lines = lines;
+        cleanup
+    }
+
+    pub fn content(&self) -> String {
+        self.lines.iter().fold(String::new(), |mut acc, line| {
+            acc.push_str(&format!("{}\n", line));
+            acc
+        })
+    }
+
+    pub fn write(&self) {
+        fs::write(&self.path, self.content()).unwrap();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    const CONTENT: &str =
+        "// rejected words\n\n[names]\nharry // character\nPotter\n\n[other]\nqu\nharry\n";
+    fn off() -> OffFile {
+        OffFile::new("").parse(CONTENT)
+    }
+    #[test]
+    fn parse_test() {
+        let off = off();
+        assert_eq!(off.words(), vec!["harry", "potter", "qu", "harry"]);
+        assert_eq!(off.lines[2], Line::Section("names".to_owned()));
+        assert_eq!(off.reason("harry"), Some("character"));
+        assert_eq!(off.reason("qu"), None);
+    }
+    #[test]
+    fn content_test() {
+        assert_eq!(off().content(), CONTENT);
+        let off = OffFile::new("").parse("  CANCIO\u{301}N // x\n");
+        assert_eq!(off.words(), vec!["canción"]);
+        assert_eq!(off.content(), "CANCIO\u{301}N // x\n");
+    }
+    #[test]
+    fn cleanup_test() {
+        let mut off = off();
+        let vocabulary: HashSet<Word> = [Word::new("qu")].into_iter().collect();
+        let cleanup = off.cleanup(&vocabulary);
+        assert_eq!(
+            cleanup.diff(),
+            "-8: qu (now in vocabulary)\n-9: harry (repeats line 4)\n"
+        );
+        assert_eq!(off.words(), vec!["harry", "potter"]);
+        assert_eq!(off.lines[0], Line::Comment("// rejected words".to_owned()));
+        assert!(off.cleanup(&vocabulary).is_empty());
+    }
+    #[test]
+    fn remove_test() {
+        let mut off = off();
+        let words: HashSet<String> = ["harry".to_owned()].into_iter().collect();
+        assert_eq!(off.remove(&words), 2);
+        assert_eq!(off.words(), vec!["potter", "qu"]);
+    }
+    #[test]
+    fn reject_test() {
+        let mut off = off();
+        assert!(!off.reject("Harry", None, None));
+        assert!(off.reject("ron", Some("character"), Some("names")));
+        assert!(off.reject("xx", None, Some("new")));
+        assert!(off.reject("zz", None, None));
+        let content = off.content();
+        assert!(content.contains("Potter\nron // character\n\n[other]"));
+        assert!(content.ends_with("harry\n\n[new]\nxx\nzz\n"));
+    }
+}
@@ -0,0 +1,101 @@
+use std::{
+    fs,
+    io::Write,
+    path::{Path, PathBuf},
+};
+
+/// One file of a [`BuildOutput`], `path` is relative to the directory the
+/// output is written to.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Entry {
+    pub path: PathBuf,
+    pub content: String,
+    /// Add `content` at the end of an existing file instead of replacing it.
+    pub append: bool,
+}
+
+/// Files produced by a run, kept in memory so they can be inspected or
+/// embedded elsewhere. Only [`BuildOutput::write`] touches the file system.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct BuildOutput {
+    pub files: Vec<Entry>,
+}
+
+impl BuildOutput {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn push<T: AsRef<Path>>(&mut self, path: T, content: String) {
+        self.files.push(Entry {
+            path: path.as_ref().to_path_buf(),
+            content,
+            append: false,
+        });
+    }
+
+    pub fn append<T: AsRef<Path>>(&mut self, path: T, content: String) {
+        self.files.push(Entry {
+            path: path.as_ref().to_path_buf(),
+            content,
+            append: true,
+        });
+    }
+
+    pub fn get<T: AsRef<Path>>(&self, path: T) -> Option<&str> {
+        self.files
+            .iter()
+            .find(|entry| entry.path == path.as_ref())
+            .map(|entry| entry.content.as_str())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.files.is_empty()
+    }
+
+    pub fn write<T: AsRef<Path>>(&self, dir: T) {
+        for entry in &self.files {
+            let path = dir.as_ref().join(&entry.path);
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent).unwrap();
+            }
+            if entry.append {
+                let mut file = fs::OpenOptions::new()
+                    .create(true)
+                    .append(true)
+                    .open(&path)
+                    .unwrap();
+                file.write_all(entry.content.as_bytes()).unwrap();
+            } else {
+                fs::write(&path, &entry.content).unwrap();
+            }
+        }
+    }
+}
+
+/// Removes `dir` and creates it again empty.
+pub fn clean<T: AsRef<Path>>(dir: T) {
+    let dir = dir.as_ref();
+    if dir.is_dir() {
+        fs::remove_dir_all(dir).unwrap();
+    }
+    fs::create_dir_all(dir).unwrap();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn write_test() {
+        let dir = std::env::temp_dir().join(format!("gamo-output-{}", std::process::id()));
+        clean(&dir);
+        let mut output = BuildOutput::new();
+        output.push("parts/a.on", "uno\n".to_owned());
+        output.append("parts/a.on", "dos\n".to_owned());
+        assert_eq!(output.get("parts/a.on"), Some("uno\n"));
+        output.write(&dir);
+        let content = fs::read_to_string(dir.join("parts/a.on")).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(content, "uno\ndos\n");
+    }
+}
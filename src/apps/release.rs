@@ -0,0 +1,294 @@
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    fmt, fs,
+    path::{Path, PathBuf},
+};
+
+use crate::apps::{
+    checksum,
+    export::{self, Format, Record},
+    output::BuildOutput,
+    Vocabulary,
+};
+
+const DIR: &str = "releases";
+
+/// `manifest.json` of a release.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Manifest {
+    pub version: usize,
+    /// Checksum of `vocabulary.csv`.
+    pub checksum: String,
+    pub total: usize,
+    /// Words per folder and tipo.
+    pub counts: BTreeMap<String, BTreeMap<String, usize>>,
+}
+
+/// A numbered snapshot of the vocabulary, kept in
+/// `releases/<version>-<checksum>` with its manifest, every word as
+/// `vocabulary.csv` and the `build` word lists.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Release {
+    pub manifest: Manifest,
+    pub records: Vec<Record>,
+}
+
+impl Release {
+    pub fn new(records: Vec<Record>, version: usize) -> Self {
+        let mut counts: BTreeMap<String, BTreeMap<String, usize>> = BTreeMap::new();
+        for record in &records {
+            *counts
+                .entry(record.folder.to_owned())
+                .or_default()
+                .entry(record.tipo.to_owned())
+                .or_default() += 1;
+        }
+        let csv = export::write(&records, Format::Csv);
+        Self {
+            manifest: Manifest {
+                version,
+                checksum: checksum::hex(csv.as_bytes()),
+                total: records.len(),
+                counts,
+            },
+            records,
+        }
+    }
+
+    pub fn name(&self) -> String {
+        format!("{}-{}", self.manifest.version, self.manifest.checksum)
+    }
+
+    /// The release folder, `build` goes into its `build` subfolder.
+    pub fn output(&self, build: BuildOutput) -> BuildOutput {
+        let mut output = BuildOutput::new();
+        let manifest = serde_json::to_string_pretty(&self.manifest).unwrap();
+        output.push("manifest.json", format!("{}\n", manifest));
+        output.push("vocabulary.csv", export::write(&self.records, Format::Csv));
+        for entry in build.files {
+            output.push(Path::new("build").join(&entry.path), entry.content);
+        }
+        output
+    }
+
+    fn open(dir: &Path) -> Result<Self, String> {
+        let read = |name: &str| {
+            fs::read_to_string(dir.join(name)).map_err(|e| format!("{}: {}", dir.display(), e))
+        };
+        let manifest = serde_json::from_str(&read("manifest.json")?)
+            .map_err(|e| format!("{}: {}", dir.display(), e))?;
+        let records = export::read(&read("vocabulary.csv")?, Format::Csv)?;
+        Ok(Self { manifest, records })
+    }
+}
+
+/// The folder holding every release.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Releases {
+    dir: PathBuf,
+}
+
+/// The releases of the working directory.
+pub fn releases() -> Releases {
+    Releases::new(DIR)
+}
+
+impl Releases {
+    pub fn new<T: AsRef<Path>>(dir: T) -> Self {
+        Self {
+            dir: dir.as_ref().to_path_buf(),
+        }
+    }
+
+    /// Version and folder of every release, oldest first.
+    pub fn list(&self) -> Vec<(usize, PathBuf)> {
+        let mut list: Vec<(usize, PathBuf)> = fs::read_dir(&self.dir)
+            .into_iter()
+            .flatten()
+            .flatten()
+            .filter_map(|entry| {
+                let name = entry.file_name().to_string_lossy().to_string();
+                let (version, _) = name.split_once('-')?;
+                Some((version.parse().ok()?, entry.path()))
+            })
+            .collect();
+        list.sort();
+        list
+    }
+
+    pub fn open(&self, version: usize) -> Result<Release, String> {
+        match self.list().into_iter().find(|(n, _)| *n == version) {
+            Some((_, dir)) => Release::open(&dir),
+            None => Err(format!("there is no release {}", version)),
+        }
+    }
+
+    pub fn latest(&self) -> Result<Option<Release>, String> {
+        self.list()
+            .last()
+            .map(|(_, dir)| Release::open(dir))
+            .transpose()
+    }
+
+    /// Writes the next release with the `build` of `vocabulary`, failing
+    /// when the vocabulary did not change since the latest one.
+    pub fn publish(&self, vocabulary: &Vocabulary, build: BuildOutput) -> Result<Release, String> {
+        let latest = self.latest()?;
+        let version = latest.as_ref().map_or(1, |n| n.manifest.version + 1);
+        let release = Release::new(vocabulary.records(None), version);
+        if let Some(latest) = latest {
+            if latest.manifest.checksum == release.manifest.checksum {
+                return Err(format!(
+                    "nothing changed since release {}",
+                    latest.manifest.version
+                ));
+            }
+        }
+        release.output(build).write(self.dir.join(release.name()));
+        Ok(release)
+    }
+}
+
+/// Words added, removed and moved to another folder or tipo between two
+/// lists of records.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Diff {
+    pub added: Vec<Record>,
+    pub removed: Vec<Record>,
+    pub moved: Vec<(Record, Record)>,
+}
+
+impl Diff {
+    pub fn new(old: &[Record], new: &[Record]) -> Self {
+        let by_word = |records: &[Record]| -> BTreeMap<String, Record> {
+            let mut acc = BTreeMap::new();
+            for record in records {
+                acc.entry(record.word.to_owned())
+                    .or_insert_with(|| record.to_owned());
+            }
+            acc
+        };
+        let old = by_word(old);
+        let new = by_word(new);
+        let mut diff = Diff::default();
+        for (word, record) in &new {
+            match old.get(word) {
+                None => diff.added.push(record.to_owned()),
+                Some(before)
+                    if (&before.folder, &before.tipo) != (&record.folder, &record.tipo) =>
+                {
+                    diff.moved.push((before.to_owned(), record.to_owned()))
+                }
+                Some(_) => {}
+            }
+        }
+        for (word, record) in old {
+            if !new.contains_key(&word) {
+                diff.removed.push(record);
+            }
+        }
+        diff
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.moved.is_empty()
+    }
+}
+
+/// Markdown changelog.
+impl fmt::Display for Diff {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let place = |record: &Record| format!("{}/{}", record.folder, record.tipo);
+        if !self.added.is_empty() {
+            writeln!(f, "## Added ({})\n", self.added.len())?;
+            for record in &self.added {
+                writeln!(f, "- {} ({})", record.word, place(record))?;
+            }
+            writeln!(f)?;
+        }
+        if !self.removed.is_empty() {
+            writeln!(f, "## Removed ({})\n", self.removed.len())?;
+            for record in &self.removed {
+                writeln!(f, "- {} ({})", record.word, place(record))?;
+            }
+            writeln!(f)?;
+        }
+        if !self.moved.is_empty() {
+            writeln!(f, "## Moved ({})\n", self.moved.len())?;
+            for (before, after) in &self.moved {
+                writeln!(
+                    f,
+                    "- {} ({} -> {})",
+                    after.word,
+                    place(before),
+                    place(after)
+                )?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::apps::{build, fixture::Fixture, Formats};
+    fn publish(releases: &Releases, vocabulary: &Vocabulary) -> Result<Release, String> {
+        let build = build::build(vocabulary, &Formats::new(), None, None);
+        releases.publish(vocabulary, build)
+    }
+    #[test]
+    fn diff_test() {
+        let old = Fixture::new()
+            .types(&["ANY", "esencial"])
+            .words("ANY", &["casa", "gato", "perro"])
+            .vocabulary()
+            .records(None);
+        let new = Fixture::new()
+            .types(&["ANY", "esencial"])
+            .words("ANY", &["casa", "loro"])
+            .words("esencial", &["gato"])
+            .vocabulary()
+            .records(None);
+        let diff = Diff::new(&old, &new);
+        assert_eq!(
+            diff.to_string(),
+            "## Added (1)\n\n- loro (4/ANY)\n\n\
+             ## Removed (1)\n\n- perro (5/ANY)\n\n\
+             ## Moved (1)\n\n- gato (4/ANY -> 4/esencial)\n\n"
+        );
+        assert!(Diff::new(&new, &new).is_empty());
+    }
+    #[test]
+    fn publish_test() {
+        let dir = std::env::temp_dir().join(format!("gamo-releases-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        let releases = Releases::new(&dir);
+        let vocabulary = Fixture::new()
+            .types(&["ANY"])
+            .words("ANY", &["casa", "perro"])
+            .vocabulary();
+        let first = publish(&releases, &vocabulary).unwrap();
+        assert_eq!(first.manifest.version, 1);
+        assert_eq!(first.manifest.counts["4"]["ANY"], 1);
+        assert!(publish(&releases, &vocabulary).is_err());
+
+        let vocabulary = Fixture::new()
+            .types(&["ANY"])
+            .words("ANY", &["casa", "gato"])
+            .vocabulary();
+        let second = publish(&releases, &vocabulary).unwrap();
+        assert_eq!(second.manifest.version, 2);
+        let name = dir.join(second.name());
+        assert!(name.join("build/ANY.on").exists());
+        assert_eq!(releases.open(1).unwrap(), first);
+        assert_eq!(releases.latest().unwrap(), Some(second.to_owned()));
+        let diff = Diff::new(&first.records, &second.records);
+        fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(diff.added[0].word, "gato");
+        assert_eq!(diff.removed[0].word, "perro");
+        assert!(releases.open(3).is_err());
+    }
+}
@@ -0,0 +1,33 @@
+use std::path::Path;
+use tiny_http::{Header, Response, Server};
+
+use crate::apps::{api::Api, booktore, journal::journal, Sphere};
+
+/// Answers the [`Api`] endpoints on `addr` until the process is stopped.
+/// `root` holds `word.off` and the `parts` of `mas`.
+pub fn run(sphere: Sphere, root: &Path, addr: &str) -> Result<(), String> {
+    let server = Server::http(addr).map_err(|e| format!("{}: {}", addr, e))?;
+    println!("Serving on http://{}", addr);
+    let mut api = Api::new(sphere, root, booktore::corpus());
+    let header = Header::from_bytes("Content-Type", "application/json; charset=utf-8").unwrap();
+    for request in server.incoming_requests() {
+        let method = request.method().as_str();
+        let response = match method {
+            "POST" => {
+                let mut paths = api.sphere.paths();
+                paths.push(root.join("word.off"));
+                let command = format!("serve {} {}", method, request.url());
+                journal().record(&command, &paths, || api.handle(method, request.url()))
+            }
+            _ => api.handle(method, request.url()),
+        };
+        println!("{} {} {}", request.method(), request.url(), response.status);
+        let response = Response::from_string(response.body)
+            .with_status_code(response.status)
+            .with_header(header.clone());
+        if let Err(error) = request.respond(response) {
+            eprintln!("{}", error);
+        }
+    }
+    Ok(())
+}
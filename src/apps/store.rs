@@ -0,0 +1,369 @@
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use crate::apps::ReadParseFile;
+
+/// Where the vocabulary words live. Words are kept in one list per folder
+/// and tipo, one word per line, blank lines included so a word keeps its
+/// line number.
+pub trait VocabularyStore {
+    fn folders(&self) -> Vec<String>;
+    fn types(&self) -> Vec<String>;
+    /// Lines of one list, empty when the list does not exist.
+    fn read(&self, folder: &str, tipo: &str) -> Vec<String>;
+    /// Replaces one list, registering `folder` when it is new.
+    fn write(&mut self, folder: &str, tipo: &str, lines: &[String]);
+    /// Renames the `from` tipo to `to` in every folder.
+    fn rename(&mut self, from: &str, to: &str);
+    /// Content of the levels file, see `level::parse`.
+    fn read_levels(&self) -> String;
+    fn write_levels(&mut self, content: &str);
+}
+
+pub(crate) fn sort_folders(folders: &mut [String]) {
+    folders.sort_by_key(|folder| folder.parse::<usize>().unwrap_or(usize::MAX));
+}
+
+/// The `<dir>/<folder>/<tipo>.on` tree. Folders and types come from
+/// `folders.on` and `types.on` in the `config` directory, or from the tree
+/// itself when there is none.
+#[derive(Debug, Clone)]
+pub struct DirStore {
+    dir: PathBuf,
+    config: Option<PathBuf>,
+}
+
+impl DirStore {
+    pub fn new<T: AsRef<Path>>(dir: T) -> Self {
+        Self {
+            dir: dir.as_ref().to_path_buf(),
+            config: None,
+        }
+    }
+
+    pub fn config<T: AsRef<Path>>(mut self, config: T) -> Self {
+        self.config = Some(config.as_ref().to_path_buf());
+        self
+    }
+
+    /// Creates the folders and empty list files that are still missing.
+    pub fn create(self) -> Self {
+        for folder in self.folders() {
+            let dir = self.dir.join(&folder);
+            if !dir.exists() {
+                fs::create_dir_all(&dir).unwrap();
+            }
+            for tipo in self.types() {
+                let path = self.path(&folder, &tipo);
+                if !path.exists() {
+                    fs::File::create(&path).unwrap();
+                }
+            }
+        }
+        self
+    }
+
+    fn path(&self, folder: &str, tipo: &str) -> PathBuf {
+        self.dir.join(folder).join(format!("{}.on", tipo))
+    }
+
+    fn config_list(&self, name: &str) -> Option<Vec<String>> {
+        let path = self.config.as_ref()?.join(name);
+        Some(ReadParseFile::new(path).split_whitespace())
+    }
+
+    fn entries(&self) -> Vec<PathBuf> {
+        let mut acc: Vec<_> = fs::read_dir(&self.dir)
+            .map(|dir| dir.flatten().map(|entry| entry.path()).collect())
+            .unwrap_or_default();
+        acc.sort();
+        acc
+    }
+}
+
+impl VocabularyStore for DirStore {
+    fn folders(&self) -> Vec<String> {
+        if let Some(list) = self.config_list("folders.on") {
+            return list;
+        }
+        let mut acc: Vec<String> = self
+            .entries()
+            .iter()
+            .filter(|path| path.is_dir())
+            .filter_map(|path| Some(path.file_name()?.to_str()?.to_owned()))
+            .collect();
+        sort_folders(&mut acc);
+        acc
+    }
+
+    fn types(&self) -> Vec<String> {
+        if let Some(list) = self.config_list("types.on") {
+            return list;
+        }
+        let mut acc: Vec<String> = vec![];
+        for path in self.entries().iter().filter(|path| path.is_dir()) {
+            for file in fs::read_dir(path).unwrap().flatten() {
+                let path = file.path();
+                if path.extension().is_some_and(|ext| ext == "on") {
+                    let tipo = path.file_stem().unwrap().to_string_lossy().to_string();
+                    if !acc.contains(&tipo) {
+                        acc.push(tipo);
+                    }
+                }
+            }
+        }
+        acc.sort();
+        acc
+    }
+
+    fn read(&self, folder: &str, tipo: &str) -> Vec<String> {
+        let mut lines: Vec<String> = fs::read_to_string(self.path(folder, tipo))
+            .unwrap_or_default()
+            .split('\n')
+            .map(|line| line.trim().to_owned())
+            .collect();
+        while lines.last().is_some_and(|line| line.is_empty()) {
+            lines.pop();
+        }
+        lines
+    }
+
+    fn write(&mut self, folder: &str, tipo: &str, lines: &[String]) {
+        let mut folders = self.folders();
+        if !folders.iter().any(|n| n == folder) {
+            fs::create_dir_all(self.dir.join(folder)).unwrap();
+            if let Some(config) = &self.config {
+                folders.push(folder.to_owned());
+                sort_folders(&mut folders);
+                fs::write(config.join("folders.on"), folders.join("\n")).unwrap();
+                for tipo in self.types() {
+                    let path = self.path(folder, &tipo);
+                    if !path.exists() {
+                        fs::File::create(&path).unwrap();
+                    }
+                }
+            }
+        }
+        let mut lines = lines.to_vec();
+        while lines.last().is_some_and(|line| line.is_empty()) {
+            lines.pop();
+        }
+        fs::write(self.path(folder, tipo), format!("{}\n", lines.join("\n"))).unwrap();
+    }
+
+    fn rename(&mut self, from: &str, to: &str) {
+        for folder in self.folders() {
+            fs::rename(self.path(&folder, from), self.path(&folder, to)).unwrap();
+        }
+    }
+
+    fn read_levels(&self) -> String {
+        fs::read_to_string(self.dir.join("levels.on")).unwrap_or_default()
+    }
+
+    fn write_levels(&mut self, content: &str) {
+        fs::create_dir_all(&self.dir).unwrap();
+        fs::write(self.dir.join("levels.on"), content).unwrap();
+    }
+}
+
+/// Everything in memory, for tests and for embedding gamo.
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MemoryStore {
+    pub folders: Vec<String>,
+    pub types: Vec<String>,
+    /// Lines by folder and tipo.
+    pub words: BTreeMap<String, BTreeMap<String, Vec<String>>>,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub levels: String,
+}
+
+impl MemoryStore {
+    pub fn new(types: &[&str]) -> Self {
+        Self {
+            types: types.iter().map(|tipo| tipo.to_string()).collect(),
+            ..Default::default()
+        }
+    }
+}
+
+impl VocabularyStore for MemoryStore {
+    fn folders(&self) -> Vec<String> {
+        self.folders.to_owned()
+    }
+
+    fn types(&self) -> Vec<String> {
+        self.types.to_owned()
+    }
+
+    fn read(&self, folder: &str, tipo: &str) -> Vec<String> {
+        self.words
+            .get(folder)
+            .and_then(|types| types.get(tipo))
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    fn write(&mut self, folder: &str, tipo: &str, lines: &[String]) {
+        if !self.folders.iter().any(|n| n == folder) {
+            self.folders.push(folder.to_owned());
+            sort_folders(&mut self.folders);
+        }
+        if !self.types.iter().any(|n| n == tipo) {
+            self.types.push(tipo.to_owned());
+        }
+        self.words
+            .entry(folder.to_owned())
+            .or_default()
+            .insert(tipo.to_owned(), lines.to_vec());
+    }
+
+    fn rename(&mut self, from: &str, to: &str) {
+        match self.types.iter().position(|n| n == from) {
+            Some(_) if self.types.iter().any(|n| n == to) => self.types.retain(|n| n != from),
+            Some(index) => self.types[index] = to.to_owned(),
+            None => {}
+        }
+        for types in self.words.values_mut() {
+            if let Some(lines) = types.remove(from) {
+                types.insert(to.to_owned(), lines);
+            }
+        }
+    }
+
+    fn read_levels(&self) -> String {
+        self.levels.to_owned()
+    }
+
+    fn write_levels(&mut self, content: &str) {
+        self.levels = content.to_owned();
+    }
+}
+
+/// The whole vocabulary in a single JSON file, saved after every change.
+#[derive(Debug, Clone)]
+pub struct JsonStore {
+    path: PathBuf,
+    memory: MemoryStore,
+}
+
+impl JsonStore {
+    pub fn open<T: AsRef<Path>>(path: T) -> Result<Self, String> {
+        let path = path.as_ref().to_path_buf();
+        let memory = match fs::read_to_string(&path) {
+            Ok(content) => {
+                serde_json::from_str(&content).map_err(|e| format!("{}: {}", path.display(), e))?
+            }
+            Err(_) => MemoryStore::default(),
+        };
+        Ok(Self { path, memory })
+    }
+
+    fn save(&self) {
+        let content = serde_json::to_string_pretty(&self.memory).unwrap();
+        fs::write(&self.path, format!("{}\n", content)).unwrap();
+    }
+}
+
+impl VocabularyStore for JsonStore {
+    fn folders(&self) -> Vec<String> {
+        self.memory.folders()
+    }
+
+    fn types(&self) -> Vec<String> {
+        self.memory.types()
+    }
+
+    fn read(&self, folder: &str, tipo: &str) -> Vec<String> {
+        self.memory.read(folder, tipo)
+    }
+
+    fn write(&mut self, folder: &str, tipo: &str, lines: &[String]) {
+        self.memory.write(folder, tipo, lines);
+        self.save();
+    }
+
+    fn rename(&mut self, from: &str, to: &str) {
+        self.memory.rename(from, to);
+        self.save();
+    }
+
+    fn read_levels(&self) -> String {
+        self.memory.read_levels()
+    }
+
+    fn write_levels(&mut self, content: &str) {
+        self.memory.write_levels(content);
+        self.save();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::apps::Vocabulary;
+    fn lines(list: &[&str]) -> Vec<String> {
+        list.iter().map(|n| n.to_string()).collect()
+    }
+    fn exercise(store: &mut dyn VocabularyStore) {
+        store.write("5", "ANY", &lines(&["perro", "", "gatos"]));
+        store.write("4", "ANY", &lines(&["casa"]));
+        assert_eq!(store.folders(), vec!["4", "5"]);
+        assert_eq!(store.read("5", "ANY"), lines(&["perro", "", "gatos"]));
+        assert!(store.read("5", "esencial").iter().all(|n| n.is_empty()));
+        store.rename("ANY", "esencial");
+        assert_eq!(store.read("4", "esencial"), lines(&["casa"]));
+        store.write_levels("1,casa,A1\n");
+        assert_eq!(store.read_levels(), "1,casa,A1\n");
+    }
+    fn temp(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("gamo-store-{}-{}", name, std::process::id()))
+    }
+    #[test]
+    fn memory_test() {
+        exercise(&mut MemoryStore::new(&["ANY", "esencial"]));
+    }
+    #[test]
+    fn json_test() {
+        let path = temp("json");
+        let mut store = JsonStore::open(&path).unwrap();
+        exercise(&mut store);
+        let reopened = JsonStore::open(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        assert_eq!(reopened.memory, store.memory);
+        assert!(JsonStore::open("Cargo.toml").is_err());
+    }
+    #[test]
+    fn vocabulary_test() {
+        let path = temp("vocabulary");
+        let words = |vocabulary: &Vocabulary| -> Vec<(String, String)> {
+            vocabulary
+                .words()
+                .iter()
+                .map(|mas| (mas.word.to_string(), mas.tipo.to_owned()))
+                .collect()
+        };
+        let mut vocabulary = Vocabulary::from_store(Box::new(JsonStore::open(&path).unwrap()));
+        vocabulary.store.write("4", "ANY", &lines(&["casa"]));
+        vocabulary.reload();
+        assert_eq!(words(&vocabulary), vec![("casa".into(), "ANY".into())]);
+        vocabulary.store.rename("ANY", "esencial");
+        let reopened = Vocabulary::from_store(Box::new(JsonStore::open(&path).unwrap()));
+        fs::remove_file(&path).unwrap();
+        assert_eq!(words(&reopened), vec![("casa".into(), "esencial".into())]);
+        assert_eq!(reopened.store.types(), vec!["esencial"]);
+    }
+    #[test]
+    fn dir_test() {
+        let dir = temp("dir");
+        let mut store = DirStore::new(&dir);
+        exercise(&mut store);
+        let content = fs::read_to_string(dir.join("5/esencial.on")).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(content, "perro\n\ngatos\n");
+    }
+}
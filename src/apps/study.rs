@@ -0,0 +1,313 @@
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{HashMap, HashSet},
+    fmt, fs,
+    io::{BufRead, Write},
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use crate::apps::Vocabulary;
+
+/// New words introduced per session, on top of the due reviews.
+const NEW_PER_DAY: usize = 20;
+const START_EASE: f64 = 2.5;
+const MIN_EASE: f64 = 1.3;
+const DIR: &str = "study";
+
+/// Days since the Unix epoch.
+pub fn today() -> u64 {
+    let secs = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs();
+    secs / 86_400
+}
+
+/// Answer of a review, the SM-2 quality is 1, 3, 4 and 5.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Grade {
+    Again,
+    Hard,
+    Good,
+    Easy,
+}
+
+impl Grade {
+    fn quality(self) -> f64 {
+        match self {
+            Grade::Again => 1.0,
+            Grade::Hard => 3.0,
+            Grade::Good => 4.0,
+            Grade::Easy => 5.0,
+        }
+    }
+
+    /// `1` to `4`, as typed in the quiz.
+    pub fn parse(input: &str) -> Option<Self> {
+        match input.trim() {
+            "1" => Some(Grade::Again),
+            "2" => Some(Grade::Hard),
+            "3" => Some(Grade::Good),
+            "4" => Some(Grade::Easy),
+            _ => None,
+        }
+    }
+}
+
+/// Review state of one word.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Card {
+    pub word: String,
+    pub repetitions: u32,
+    /// Days until the next review.
+    pub interval: u64,
+    pub ease: f64,
+    pub due: u64,
+    pub lapses: u32,
+    /// Day of the last review, `None` while the word is new.
+    pub last: Option<u64>,
+}
+
+impl Card {
+    pub fn new(word: &str, today: u64) -> Self {
+        Self {
+            word: word.to_owned(),
+            repetitions: 0,
+            interval: 0,
+            ease: START_EASE,
+            due: today,
+            lapses: 0,
+            last: None,
+        }
+    }
+
+    pub fn is_new(&self) -> bool {
+        self.last.is_none()
+    }
+
+    /// SM-2: a failed word starts over tomorrow, a known one waits 1, 6 and
+    /// then `interval * ease` days.
+    pub fn review(&mut self, grade: Grade, today: u64) {
+        let quality = grade.quality();
+        if grade == Grade::Again {
+            self.repetitions = 0;
+            self.interval = 1;
+            self.lapses += 1;
+        } else {
+            self.interval = match self.repetitions {
+                0 => 1,
+                1 => 6,
+                _ => (self.interval as f64 * self.ease).round() as u64,
+            };
+            self.repetitions += 1;
+        }
+        let miss = 5.0 - quality;
+        self.ease = (self.ease + 0.1 - miss * (0.08 + miss * 0.02)).max(MIN_EASE);
+        self.due = today + self.interval;
+        self.last = Some(today);
+    }
+}
+
+/// Cards of one learner, kept in `study/<learner>.json` in vocabulary order.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Deck {
+    pub path: PathBuf,
+    pub cards: Vec<Card>,
+}
+
+impl Deck {
+    /// The deck of `learner`, empty when it has no file yet.
+    pub fn learner(learner: &str) -> Result<Self, String> {
+        let valid = |letter: char| letter.is_alphanumeric() || letter == '-' || letter == '_';
+        if learner.is_empty() || !learner.chars().all(valid) {
+            return Err(format!("({}) is not a valid learner name", learner));
+        }
+        Deck::open(Path::new(DIR).join(format!("{}.json", learner)))
+    }
+
+    pub fn open<T: AsRef<Path>>(path: T) -> Result<Self, String> {
+        let path = path.as_ref().to_path_buf();
+        let cards = match fs::read_to_string(&path) {
+            Ok(content) => {
+                serde_json::from_str(&content).map_err(|e| format!("{}: {}", path.display(), e))?
+            }
+            Err(_) => vec![],
+        };
+        Ok(Self { path, cards })
+    }
+
+    pub fn save(&self) {
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent).unwrap();
+        }
+        let content = serde_json::to_string_pretty(&self.cards).unwrap();
+        fs::write(&self.path, format!("{}\n", content)).unwrap();
+    }
+
+    /// Adds a card for every new vocabulary word and drops the cards of
+    /// words that left the vocabulary.
+    pub fn sync(&mut self, vocabulary: &Vocabulary, today: u64) {
+        let words: Vec<&str> = vocabulary
+            .words()
+            .iter()
+            .map(|mas| mas.word.as_str())
+            .collect();
+        let known: HashSet<&str> = words.iter().copied().collect();
+        self.cards.retain(|card| known.contains(card.word.as_str()));
+        let cards: HashSet<String> = self.cards.iter().map(|card| card.word.clone()).collect();
+        for word in words {
+            if !cards.contains(word) {
+                self.cards.push(Card::new(word, today));
+            }
+        }
+    }
+
+    /// Positions of the cards to study: due reviews, oldest first, then up
+    /// to `NEW_PER_DAY` new words.
+    pub fn due(&self, today: u64) -> Vec<usize> {
+        let mut reviews: Vec<usize> = (0..self.cards.len())
+            .filter(|n| !self.cards[*n].is_new() && self.cards[*n].due <= today)
+            .collect();
+        reviews.sort_by_key(|n| self.cards[*n].due);
+        let new = (0..self.cards.len()).filter(|n| self.cards[*n].is_new());
+        reviews.extend(new.take(NEW_PER_DAY));
+        reviews
+    }
+}
+
+/// Outcome of a quiz session.
+#[derive(Debug, Default, PartialEq)]
+pub struct Session {
+    pub reviewed: usize,
+    pub again: usize,
+}
+
+impl fmt::Display for Session {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} reviewed, {} to repeat", self.reviewed, self.again)
+    }
+}
+
+/// Asks every due card of `deck`: the word is shown, Enter reveals its
+/// example sentence and the answer is graded from 1 (again) to 4 (easy).
+/// `q` stops, the deck is saved after every answer so none is lost.
+pub fn quiz<R: BufRead, W: Write>(
+    deck: &mut Deck,
+    examples: &HashMap<String, (usize, String)>,
+    today: u64,
+    input: &mut R,
+    output: &mut W,
+) -> Session {
+    let mut session = Session::default();
+    let due = deck.due(today);
+    let mut line = String::new();
+    let mut read = |line: &mut String| {
+        line.clear();
+        input.read_line(line).unwrap_or(0) > 0 && line.trim() != "q"
+    };
+    for (index, position) in due.iter().enumerate() {
+        let card = &mut deck.cards[*position];
+        writeln!(output, "\n[{}/{}] {}", index + 1, due.len(), card.word).unwrap();
+        if !read(&mut line) {
+            break;
+        }
+        match examples.get(&card.word) {
+            Some((_, sentence)) => writeln!(output, "  {}", sentence).unwrap(),
+            None => writeln!(output, "  (no example)").unwrap(),
+        }
+        let grade = loop {
+            write!(output, "1 again, 2 hard, 3 good, 4 easy: ").unwrap();
+            output.flush().unwrap();
+            if !read(&mut line) {
+                return session;
+            }
+            if let Some(grade) = Grade::parse(&line) {
+                break grade;
+            }
+        };
+        card.review(grade, today);
+        deck.save();
+        session.reviewed += 1;
+        if grade == Grade::Again {
+            session.again += 1;
+        }
+    }
+    session
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::apps::fixture::Fixture;
+    #[test]
+    fn review_test() {
+        let mut card = Card::new("casa", 10);
+        assert!(card.is_new());
+        card.review(Grade::Good, 10);
+        assert_eq!((card.interval, card.due), (1, 11));
+        card.review(Grade::Good, 11);
+        assert_eq!((card.interval, card.due), (6, 17));
+        card.review(Grade::Easy, 17);
+        assert_eq!(card.interval, 15);
+        assert!((card.ease - 2.6).abs() < 1e-9);
+        card.review(Grade::Again, 32);
+        assert_eq!((card.repetitions, card.interval, card.lapses), (0, 1, 1));
+        for _ in 0..10 {
+            card.review(Grade::Again, 33);
+        }
+        assert_eq!(card.ease, MIN_EASE);
+    }
+    #[test]
+    fn deck_test() {
+        let vocabulary = Fixture::new()
+            .types(&["ANY"])
+            .words("ANY", &["casa", "perro", "gato"])
+            .vocabulary();
+        let path = std::env::temp_dir().join(format!("gamo-study-{}.json", std::process::id()));
+        let mut deck = Deck::open(&path).unwrap();
+        deck.sync(&vocabulary, 5);
+        assert_eq!(deck.due(5), vec![0, 1, 2]);
+        deck.cards[1].review(Grade::Good, 5);
+        deck.cards[0].review(Grade::Good, 4);
+        assert_eq!(deck.due(5), vec![0, 2]);
+        deck.save();
+        let reopened = Deck::open(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        assert_eq!(reopened, deck);
+        assert!(Deck::learner("../x").is_err());
+    }
+    #[test]
+    fn quiz_test() {
+        let vocabulary = Fixture::new()
+            .types(&["ANY"])
+            .words("ANY", &["casa", "perro", "gato"])
+            .vocabulary();
+        let path = std::env::temp_dir().join(format!("gamo-quiz-{}.json", std::process::id()));
+        let mut deck = Deck::open(&path).unwrap();
+        deck.sync(&vocabulary, 0);
+        let examples: HashMap<_, _> = [("casa".to_owned(), (3, "una casa".to_owned()))].into();
+        let mut output = vec![];
+        let session = quiz(
+            &mut deck,
+            &examples,
+            0,
+            &mut "\n3\n\nx\n1\nq\n".as_bytes(),
+            &mut output,
+        );
+        assert_eq!(
+            session,
+            Session {
+                reviewed: 2,
+                again: 1
+            }
+        );
+        let output = String::from_utf8(output).unwrap();
+        assert!(output.contains("[1/3] casa\n  una casa"));
+        assert!(output.contains("[2/3] gato\n  (no example)"));
+        assert_eq!(deck.due(1), vec![0, 1, 2]);
+        let saved = Deck::open(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        assert_eq!(saved, deck);
+    }
+}
@@ -0,0 +1,178 @@
+use crate::apps::normal;
+
+pub mod rule {
+    /// Shortest and longest word length, in letters.
+    pub struct Length {}
+    impl Length {
+        pub fn min() -> usize {
+            2
+        }
+        pub fn max() -> usize {
+            25
+        }
+    }
+    pub fn is_min(word: &str) -> bool {
+        word.chars().count() < Length::min()
+    }
+    pub fn is_max(word: &str) -> bool {
+        word.chars().count() > Length::max()
+    }
+}
+pub struct English;
+impl English {
+    fn start() -> i32 {
+        97
+    }
+    fn end() -> i32 {
+        122
+    }
+
+    pub fn is_range(value: i32) -> bool {
+        value >= English::start() && value <= English::end()
+    }
+    pub fn valid_english(input: &str) -> bool {
+        for letter in input.chars() {
+            if !English::is_range(letter as i32) {
+                return false;
+            }
+        }
+        !input.is_empty()
+    }
+}
+pub struct Str;
+impl Str {
+    pub fn rm_start(input: &str) -> &str {
+        let mut start = 0;
+        let mut list = input.chars();
+        let mut current = list.next();
+        while let Some(letter) = current {
+            if !English::is_range(letter as i32) {
+                start += letter.len_utf8();
+            } else {
+                return &input[start..input.len()];
+            }
+            current = list.next();
+        }
+        &input[start..input.len()]
+    }
+    pub fn rm_end(input: &str) -> &str {
+        let mut end = Str::utf_count(input);
+        let mut list = input.chars();
+        let mut current = list.next_back();
+        while let Some(letter) = current {
+            if !English::is_range(letter as i32) {
+                end -= letter.len_utf8();
+            } else {
+                return &input[0..end];
+            }
+            current = list.next_back();
+        }
+        &input[0..end]
+    }
+    pub fn rm_start_end(input: &str) -> &str {
+        Str::rm_end(Str::rm_start(input))
+    }
+    pub fn valid_english(input: &str) -> bool {
+        English::valid_english(input)
+    }
+    fn utf_count(input: &str) -> usize {
+        input.chars().fold(0, |acc, letter| acc + letter.len_utf8())
+    }
+}
+
+pub fn parse_word(word: &str) -> String {
+    let word = word.trim().to_lowercase();
+    Str::rm_start_end(&word).to_owned()
+}
+
+/// Tokenizer for words that are filed by hand, e.g. by `import`. Unlike
+/// [`parse_word`] it keeps accented letters: the word is lowercased,
+/// NFC-normalized and stripped of surrounding punctuation, and it is only
+/// accepted when every character left is a letter and its length fits
+/// [`rule::Length`].
+pub fn word(input: &str) -> Option<String> {
+    let word = normal::word(input);
+    let word = word.trim_matches(|letter: char| !letter.is_alphabetic());
+    if word.is_empty() || rule::is_min(word) || rule::is_max(word) {
+        return None;
+    }
+    if !word.chars().all(|letter| letter.is_alphabetic()) {
+        return None;
+    }
+    Some(word.to_owned())
+}
+
+/// Vocabulary folder of `word`: its length in letters, so "canción" goes
+/// to `7` like any other seven letter word.
+pub fn folder(word: &str) -> String {
+    word.chars().count().to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+    #[test]
+    fn valid_english_test() {
+        assert!(Str::valid_english("z"));
+        assert!(Str::valid_english("ab"));
+        assert!(!Str::valid_english(""));
+    }
+    #[test]
+    fn rm_start_end_test() {
+        assert_eq!(Str::rm_start_end("  "), "");
+        assert_eq!(Str::rm_start_end(" hello "), "hello");
+        assert_eq!(Str::rm_start_end("1/#*hello1/#*"), "hello");
+    }
+    #[test]
+    fn rm_start_test() {
+        assert_eq!(Str::rm_start("  "), "");
+        assert_eq!(Str::rm_start(" hello"), "hello");
+        assert_eq!(Str::rm_start("1/#*hello"), "hello");
+    }
+    #[test]
+    fn rm_end_test() {
+        assert_eq!(Str::rm_end("  "), "");
+        assert_eq!(Str::rm_end("hello "), "hello");
+        assert_eq!(Str::rm_end("hello1/#*"), "hello");
+    }
+    #[test]
+    fn parse_word_test() {
+        assert_eq!(parse_word(" ¿Hola?"), "hola");
+    }
+    #[test]
+    fn word_test() {
+        assert_eq!(word(" ¿Canción?"), Some("canción".to_owned()));
+        assert_eq!(word("CANCIO\u{301}N,"), Some("canción".to_owned()));
+        assert_eq!(word("a"), None);
+        assert_eq!(word("dos-tres"), None);
+        assert_eq!(word("123"), None);
+        assert_eq!(folder("canción"), "7");
+        assert_eq!(folder("casa"), "4");
+        let longest = format!("{}ñ", "a".repeat(rule::Length::max() - 1));
+        assert_eq!(word(&longest), Some(longest.to_owned()));
+        assert_eq!(word(&format!("{}ñ", longest)), None);
+        assert!(rule::is_min("ñ"));
+        assert!(!rule::is_min("ñá"));
+    }
+    proptest! {
+        #[test]
+        fn rm_start_end_prop(input in "\\PC*") {
+            let word = Str::rm_start_end(&input);
+            prop_assert_eq!(Str::rm_start_end(word), word);
+            prop_assert_eq!(Str::rm_start(word), word);
+            prop_assert_eq!(Str::rm_end(word), word);
+        }
+        #[test]
+        fn parse_word_prop(input in "\\PC*") {
+            let word = parse_word(&input);
+            prop_assert_eq!(parse_word(&word), word);
+        }
+        #[test]
+        fn word_prop(input in "\\PC*") {
+            if let Some(word) = word(&input) {
+                prop_assert_eq!(super::word(&word), Some(word));
+            }
+        }
+    }
+}
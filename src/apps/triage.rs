@@ -0,0 +1,209 @@
+use std::{
+    collections::HashMap,
+    fmt,
+    io::{BufRead, Write},
+};
+
+use crate::apps::{booktore, case::Casing, import::Import, off::OffFile, token, Vocabulary};
+
+/// Example sentences shown for every candidate.
+const EXAMPLES: usize = 3;
+
+/// What to do with one candidate, as typed at the prompt.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Decision {
+    Accept(String),
+    Reject(Option<String>),
+    Skip,
+    Quit,
+}
+
+impl Decision {
+    /// The number or the name of a tipo accepts, `r [reason]` rejects, an
+    /// empty line or `s` skips and `q` quits.
+    pub fn parse(input: &str, types: &[String]) -> Option<Self> {
+        let input = input.trim();
+        match input {
+            "" | "s" => return Some(Decision::Skip),
+            "q" => return Some(Decision::Quit),
+            "r" => return Some(Decision::Reject(None)),
+            _ => {}
+        }
+        if let Some(reason) = input.strip_prefix("r ") {
+            return Some(Decision::Reject(Some(reason.trim().to_owned())));
+        }
+        let tipo = match input.parse::<usize>() {
+            Ok(index) => types.get(index.checked_sub(1)?)?,
+            Err(_) => types.iter().find(|tipo| *tipo == input)?,
+        };
+        Some(Decision::Accept(tipo.to_owned()))
+    }
+}
+
+/// Outcome of a triage session.
+#[derive(Debug, Default, PartialEq)]
+pub struct Triage {
+    pub accepted: usize,
+    pub rejected: usize,
+    pub skipped: usize,
+}
+
+impl fmt::Display for Triage {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} accepted, {} rejected, {} skipped",
+            self.accepted, self.rejected, self.skipped
+        )
+    }
+}
+
+/// Walks `candidates` one by one with their corpus examples. Accepted words
+/// are imported into the chosen tipo, in the folder of their length, and
+/// rejected ones appended to `off`; both are written right away so quitting
+/// at any point keeps every decision.
+pub fn run<R: BufRead, W: Write>(
+    vocabulary: &mut Vocabulary,
+    off: &mut OffFile,
+    candidates: &[String],
+    examples: &HashMap<String, (usize, String)>,
+    casing: &Casing,
+    input: &mut R,
+    output: &mut W,
+) -> Triage {
+    let mut triage = Triage::default();
+    let types = vocabulary.store.types();
+    let menu: Vec<String> = types
+        .iter()
+        .enumerate()
+        .map(|(index, tipo)| format!("{} {}", index + 1, tipo))
+        .collect();
+    let mut line = String::new();
+    for (index, word) in candidates.iter().enumerate() {
+        writeln!(
+            output,
+            "\n[{}/{}] {} (folder {})",
+            index + 1,
+            candidates.len(),
+            word,
+            token::folder(word)
+        )
+        .unwrap();
+        match examples.get(word) {
+            Some((count, sentences)) => {
+                writeln!(output, "  {} in the corpus", count).unwrap();
+                for sentence in booktore::examples(sentences).iter().take(EXAMPLES) {
+                    writeln!(output, "  {}", sentence).unwrap();
+                }
+            }
+            None => writeln!(output, "  (no example)").unwrap(),
+        }
+        let decision = loop {
+            write!(output, "{}, r reject, s skip, q quit: ", menu.join(", ")).unwrap();
+            output.flush().unwrap();
+            line.clear();
+            if input.read_line(&mut line).unwrap_or(0) == 0 {
+                return triage;
+            }
+            if let Some(decision) = Decision::parse(&line, &types) {
+                break decision;
+            }
+        };
+        match decision {
+            Decision::Accept(tipo) => {
+                let words: Vec<String> = off.words().iter().map(|n| n.to_string()).collect();
+                let import = Import::plan(&[word.to_owned()], &tipo, vocabulary, &words, casing);
+                import.apply(vocabulary);
+                for mas in &import.added {
+                    writeln!(
+                        output,
+                        "added {} to {}/{}.on",
+                        mas.word, mas.folder, mas.tipo
+                    )
+                    .unwrap();
+                    triage.accepted += 1;
+                }
+                for (word, reason) in &import.skipped {
+                    writeln!(output, "skipped {}: {}", word, reason).unwrap();
+                }
+            }
+            Decision::Reject(reason) => {
+                if off.reject(word, reason.as_deref(), None) {
+                    off.write();
+                    triage.rejected += 1;
+                }
+            }
+            Decision::Skip => triage.skipped += 1,
+            Decision::Quit => break,
+        }
+    }
+    triage
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::apps::fixture::Fixture;
+    use std::fs;
+    #[test]
+    fn parse_test() {
+        let types = vec!["ANY".to_owned(), "esencial".to_owned()];
+        let parse = |input| Decision::parse(input, &types);
+        assert_eq!(parse("2\n"), Some(Decision::Accept("esencial".to_owned())));
+        assert_eq!(parse("ANY"), Some(Decision::Accept("ANY".to_owned())));
+        assert_eq!(parse("r"), Some(Decision::Reject(None)));
+        assert_eq!(
+            parse("r  typo "),
+            Some(Decision::Reject(Some("typo".to_owned())))
+        );
+        assert_eq!(parse(""), Some(Decision::Skip));
+        assert_eq!(parse("q"), Some(Decision::Quit));
+        assert_eq!(parse("0"), None);
+        assert_eq!(parse("3"), None);
+        assert_eq!(parse("nombres"), None);
+    }
+    #[test]
+    fn run_test() {
+        let mut vocabulary = Fixture::new()
+            .types(&["ANY", "esencial"])
+            .words("ANY", &["casa"])
+            .vocabulary();
+        let path = std::env::temp_dir().join(format!("gamo-triage-{}.off", std::process::id()));
+        fs::write(&path, "// rejected\n").unwrap();
+        let mut off = OffFile::read(&path);
+        let candidates: Vec<String> = ["perro", "xyz", "gato", "loro", "mesa"]
+            .iter()
+            .map(|n| n.to_string())
+            .collect();
+        let examples: HashMap<_, _> =
+            [("perro".to_owned(), (2, "un perro \nel perro \n".to_owned()))].into();
+        let mut output = vec![];
+        let triage = run(
+            &mut vocabulary,
+            &mut off,
+            &candidates,
+            &examples,
+            &Casing::default(),
+            &mut "2\nx\nr typo\n\nANY\nq\n".as_bytes(),
+            &mut output,
+        );
+        let written = fs::read_to_string(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        assert_eq!(
+            triage,
+            Triage {
+                accepted: 2,
+                rejected: 1,
+                skipped: 1
+            }
+        );
+        assert_eq!(written, "// rejected\nxyz // typo\n");
+        assert_eq!(vocabulary.store.read("5", "esencial"), vec!["perro"]);
+        assert_eq!(vocabulary.store.read("4", "ANY"), vec!["casa", "loro"]);
+        let output = String::from_utf8(output).unwrap();
+        assert!(output.contains("[1/5] perro (folder 5)\n  2 in the corpus\n  un perro\n"));
+        assert!(output.contains("1 ANY, 2 esencial, r reject, s skip, q quit: "));
+        assert!(output.contains("[2/5] xyz (folder 3)\n  (no example)"));
+        assert!(!output.contains("added mesa"));
+    }
+}
@@ -1,58 +1,227 @@
 pub mod apps;
+use apps::anki::DeckBy;
 use apps::booktore;
+use apps::build;
+use apps::cloze;
+use apps::export::{self, Format};
+use apps::level::Level;
+use apps::output::{self, BuildOutput};
+use apps::release::{self, Diff};
+use apps::store::DirStore;
+use apps::study::{self, Deck};
 use apps::Sphere;
-use std::{fs, path::Path};
+use apps::{import, journal, mas, off::OffFile, triage};
+use std::{collections::BTreeMap, fs, io, path::Path};
 
-fn get_folder_path(folder_name: &str) -> &Path {
-    if std::path::Path::new(folder_name).is_dir() {
-        fs::remove_dir_all(folder_name).unwrap();
-    }
-
-    fs::create_dir_all(folder_name).unwrap();
-    Path::new(folder_name)
+/// Writes `output` into a freshly emptied `folder_name`.
+fn write_folder(folder_name: &str, output: &BuildOutput) {
+    output::clean(folder_name);
+    output.write(folder_name);
 }
 
-
 pub fn write_booktore(sphere: &Sphere) {
     println!("\nBOOKTORE Running...");
     let store = booktore::init_get_system();
-    let root = get_folder_path("booktore");
-    for (tipo, data) in &sphere.vocabulary.data {
-        let mut list: Vec<_> = data.iter().map(|n| n.word.to_owned()).collect();
-        list.sort_by(|a, b| {
-            let value_a = store.get(a).map_or(0, |x| x.0);
-            let value_b = store.get(b).map_or(0, |x| x.0);
-            value_b.cmp(&value_a) // Reversed order
-        });
+    fs::write("palabras.on", store.palabras()).unwrap();
+    write_folder(
+        "booktore",
+        &build::booktore(&sphere.vocabulary, &store.words),
+    );
+}
+pub fn write_anki(sphere: &Sphere, deck_by: DeckBy) {
+    println!("\nANKI Running...");
+    let store = booktore::init_get_system();
+    let output = build::anki(&sphere.vocabulary, &store.words, deck_by);
+    write_folder("anki", &output);
+    for entry in &output.files {
+        let cards = entry.content.lines().filter(|n| !n.starts_with('#'));
+        println!("{}: {}", entry.path.display(), cards.count());
+    }
+}
 
-        let path = root.join(format!("{}.off", tipo));
-        booktore::write_to_file_system(&path, &list, &store);
+pub fn write_cloze(sphere: &Sphere) {
+    println!("\nCLOZE Running...");
+    let store = booktore::init_get_system();
+    let list = cloze::exercises(&sphere.vocabulary, &store.words);
+    write_folder("cloze", &build::cloze(&list));
+    let mut count: BTreeMap<&str, usize> = BTreeMap::new();
+    for exercise in &list {
+        *count.entry(&exercise.tipo).or_default() += 1;
+    }
+    for (tipo, total) in count {
+        println!("{}.txt: {}", tipo, total);
     }
 }
-pub fn write_build(sphere: &Sphere, keys: bool) {
-    println!("\nBUILD Running...");
-    let root = get_folder_path("build");
-
-    for (tipo, list) in &sphere.vocabulary.data {
-        let contents = list
-            .iter()
-            .enumerate()
-            .fold(String::new(), |mut acc, (rank, mas)| {
-                let content = match keys {
-                    false => format!("{}\n", mas.word),
-                    true => format!("{},{},s\n", rank + 1, mas.word),
-                };
-
-                acc.push_str(&content);
-                acc
-            });
-
-        let path = root.join(format!("{}.on", tipo));
-
-        if contents.len() > 0 {
-            fs::write(path, contents).unwrap();
+
+pub fn write_export(sphere: &Sphere, format: Format, path: &Path, corpus: bool) {
+    println!("\nEXPORT Running...");
+    let store = corpus.then(booktore::init_get_system);
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).unwrap();
+    }
+    let words = store.as_ref().map(|store| &store.words);
+    fs::write(path, sphere.vocabulary.export(format, words)).unwrap();
+    println!(
+        "{}: {} words",
+        path.display(),
+        sphere.vocabulary.words().len()
+    );
+}
+
+pub fn read_export(path: &Path, dir: &Path) -> Result<usize, String> {
+    let content = fs::read_to_string(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+    let format = Format::detect(path, &content)
+        .ok_or_else(|| format!("{}: unknown export format", path.display()))?;
+    let records = export::read(&content, format)?;
+    journal::journal().record("rebuild", &[dir], || {
+        export::rebuild(&mut DirStore::new(dir), &records)
+    });
+    Ok(records.len())
+}
+
+pub fn write_levels(sphere: &mut Sphere) {
+    println!("\nLEVELS Running...");
+    let store = booktore::init_get_system();
+    let paths = sphere.paths();
+    journal::journal().record("levels", &paths, || {
+        sphere.vocabulary.assign_levels(&store.words)
+    });
+    let mut count: Vec<_> = Vec::new();
+    for mas in sphere.vocabulary.words() {
+        match count.iter_mut().find(|(level, _)| *level == mas.level) {
+            Some((_, total)) => *total += 1,
+            None => count.push((mas.level, 1)),
         }
     }
+    count.sort();
+    for (level, total) in count {
+        match level {
+            Some(level) => println!("{}: {}", level, total),
+            None => println!("unranked: {}", total),
+        }
+    }
+}
+
+/// Quiz of the words `learner` has due today, with corpus examples.
+pub fn study(sphere: &Sphere, learner: &str) -> Result<(), String> {
+    println!("\nSTUDY Running...");
+    let mut deck = Deck::learner(learner)?;
+    let today = study::today();
+    deck.sync(&sphere.vocabulary, today);
+    let due = deck.due(today);
+    if due.is_empty() {
+        println!("{}: nothing to study today", learner);
+        return Ok(());
+    }
+    let words: Vec<String> = due.iter().map(|n| deck.cards[*n].word.clone()).collect();
+    let examples = booktore::examples_of(&words);
+    let session = study::quiz(
+        &mut deck,
+        &examples.words,
+        today,
+        &mut io::stdin().lock(),
+        &mut io::stdout(),
+    );
+    println!("\n{}: {}", learner, session);
+    Ok(())
+}
+
+/// Walks the `mas` candidates of `name` and files each one into the
+/// vocabulary or `word.off` as it is decided.
+pub fn triage(sphere: &mut Sphere, name: &str) {
+    println!("\nTRIAGE Running...");
+    let runner = mas::Mas::new().setup(name);
+    let not_allow: Vec<_> = sphere
+        .vocabulary
+        .words()
+        .iter()
+        .map(|mas| mas.word)
+        .collect();
+    let candidates = runner.candidates(&not_allow).candidates;
+    if candidates.is_empty() {
+        println!("no candidates left");
+        return;
+    }
+    let examples = booktore::examples_of(&candidates);
+    let mut off = OffFile::read(runner.path_off());
+    let casing = import::casing();
+    let mut paths = sphere.paths();
+    paths.push(runner.path_off().to_path_buf());
+    let result = journal::journal().record("triage", &paths, || {
+        triage::run(
+            &mut sphere.vocabulary,
+            &mut off,
+            &candidates,
+            &examples.words,
+            &casing,
+            &mut io::stdin().lock(),
+            &mut io::stdout(),
+        )
+    });
+    println!("\n{}", result);
+}
+
+/// Reverts the last `count` journaled operations.
+pub fn undo(count: usize) -> Result<(), String> {
+    let undone = journal::journal().undo(count)?;
+    if undone.is_empty() {
+        println!("nothing to undo");
+    }
+    for entry in undone {
+        println!("undone {}", entry);
+    }
+    Ok(())
+}
+
+/// Operations `undo` can revert, most recent first.
+pub fn history() {
+    for entry in journal::journal().history() {
+        println!("{}", entry);
+    }
+}
+
+/// Writes the next numbered snapshot of the vocabulary into `releases`.
+pub fn write_release(sphere: &Sphere) -> Result<(), String> {
+    println!("\nRELEASE Running...");
+    let build = build::build(&sphere.vocabulary, &sphere.config.formats, None, None);
+    let release = release::releases().publish(&sphere.vocabulary, build)?;
+    println!("{}: {} words", release.name(), release.manifest.total);
+    Ok(())
+}
+
+/// Changelog from release `from`, the latest one by default, to release
+/// `to` or the current vocabulary.
+pub fn diff(sphere: &Sphere, from: Option<usize>, to: Option<usize>) -> Result<(), String> {
+    let releases = release::releases();
+    let old = match from {
+        Some(version) => releases.open(version)?,
+        None => releases.latest()?.ok_or("there is no release yet")?,
+    };
+    let (name, records) = match to {
+        Some(version) => (version.to_string(), releases.open(version)?.records),
+        None => ("current".to_owned(), sphere.vocabulary.records(None)),
+    };
+    let diff = Diff::new(&old.records, &records);
+    println!("# {} -> {}\n", old.manifest.version, name);
+    match diff.is_empty() {
+        true => println!("no changes"),
+        false => print!("{}", diff),
+    }
+    Ok(())
+}
+
+/// Word lists in the formats of `config/formats.on`, ranked by corpus
+/// frequency when `corpus` is set.
+pub fn write_build(sphere: &Sphere, corpus: bool, max_level: Option<Level>) {
+    println!("\nBUILD Running...");
+    let store = corpus.then(booktore::init_get_system);
+    let output = build::build(
+        &sphere.vocabulary,
+        &sphere.config.formats,
+        store.as_ref().map(|store| &store.words),
+        max_level,
+    );
+    write_folder("build", &output);
 }
 
 pub fn start(name: &str) -> Sphere {
@@ -1,9 +1,121 @@
-use gamo::apps::Sphere;
+use gamo::apps::{
+    anki::DeckBy, deny, export::Format, extract_forbid_words, import, journal::journal,
+    level::Level, mas::Missing, Sphere,
+};
+use std::{
+    env,
+    path::{Path, PathBuf},
+    process,
+};
 static WRITE_BUILD: bool = false;
 static RUN_MAS: bool = true;
 static WRITE_BOOKTORE: bool = false;
+static BUILD_LEVEL: Option<Level> = None;
+/// Ranks the build by corpus frequency instead of the stored levels.
+static BUILD_CORPUS: bool = false;
+
+fn command(args: &[String]) -> bool {
+    match args.first().map(|a| a.as_str()) {
+        Some("deny") => deny::run(&args[1..], || extract_forbid_words(&gamo::start(""))),
+        Some("anki") => match args.get(1).map(|a| a.as_str()) {
+            Some("length") => gamo::write_anki(&gamo::start(""), DeckBy::Length),
+            Some("tipo") | None => gamo::write_anki(&gamo::start(""), DeckBy::Tipo),
+            Some(other) => eprintln!("({}) is not a deck option, use tipo or length", other),
+        },
+        Some("export") => match args.get(1).map(|a| a.parse::<Format>()) {
+            Some(Ok(format)) => {
+                let corpus = args.iter().any(|a| a == "--corpus");
+                let path = args
+                    .get(2)
+                    .filter(|a| *a != "--corpus")
+                    .map(PathBuf::from)
+                    .unwrap_or_else(|| {
+                        PathBuf::from(format!("export/vocabulary.{}", format.ext()))
+                    });
+                gamo::write_export(&gamo::start(""), format, &path, corpus);
+            }
+            Some(Err(error)) => eprintln!("{}", error),
+            None => eprintln!("usage: export <json|json-flat|jsonl|csv> [path] [--corpus]"),
+        },
+        Some("rebuild") => match (args.get(1), args.get(2)) {
+            (Some(path), Some(dir)) => match gamo::read_export(Path::new(path), Path::new(dir)) {
+                Ok(total) => println!("{}: {} words", dir, total),
+                Err(error) => eprintln!("{}", error),
+            },
+            _ => eprintln!("usage: rebuild <export file> <vocabulary dir>"),
+        },
+        Some("import") => match (args.get(1), args.get(2)) {
+            (Some(path), Some(tipo)) => {
+                let name = args.get(3).map_or("", |a| a.as_str());
+                let mut sphere = gamo::start(name);
+                let paths = sphere.paths();
+                let import = journal().record("import", &paths, || {
+                    import::run(&mut sphere.vocabulary, Path::new(path), tipo)
+                });
+                match import {
+                    Ok(import) => import.report(),
+                    Err(error) => eprintln!("{}", error),
+                }
+            }
+            _ => eprintln!("usage: import <list|csv|json> <tipo> [aparter]"),
+        },
+        Some("study") => {
+            let learner = args.get(1).map_or("default", |a| a.as_str());
+            if let Err(error) = gamo::study(&gamo::start(""), learner) {
+                eprintln!("{}", error);
+            }
+        }
+        Some("triage") => {
+            let name = args.get(1).map_or("", |a| a.as_str());
+            gamo::triage(&mut gamo::start(name), name)
+        }
+        Some("undo") => match args.get(1).map_or(Ok(1), |a| a.parse::<usize>()) {
+            Ok(count) => {
+                if let Err(error) = gamo::undo(count) {
+                    eprintln!("{}", error);
+                }
+            }
+            Err(_) => eprintln!("usage: undo [count]"),
+        },
+        Some("history") => gamo::history(),
+        Some("release") => {
+            if let Err(error) = gamo::write_release(&gamo::start("")) {
+                eprintln!("{}", error);
+            }
+        }
+        Some("diff") => {
+            let version = |index: usize| args.get(index).map(|a| a.parse::<usize>());
+            match (version(1).transpose(), version(2).transpose()) {
+                (Ok(from), Ok(to)) => {
+                    if let Err(error) = gamo::diff(&gamo::start(""), from, to) {
+                        eprintln!("{}", error);
+                    }
+                }
+                _ => eprintln!("usage: diff [from release] [to release]"),
+            }
+        }
+        Some("cloze") => gamo::write_cloze(&gamo::start("")),
+        #[cfg(feature = "serve")]
+        Some("serve") => {
+            let addr = args.get(1).map_or("127.0.0.1:8080", |a| a.as_str());
+            let sphere = gamo::start("");
+            if let Err(error) = gamo::apps::serve::run(sphere, Path::new("."), addr) {
+                eprintln!("{}", error);
+            }
+        }
+        #[cfg(not(feature = "serve"))]
+        Some("serve") => eprintln!("serve needs the serve feature: cargo run --features serve"),
+        Some("levels") => gamo::write_levels(&mut gamo::start("")),
+        _ => return false,
+    }
+    true
+}
 
 fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    if command(&args) {
+        return;
+    }
     // put line below in the empty input
     // aparter
     let name = "aparter";
@@ -11,12 +123,27 @@ fn main() {
 
     let gamo = gamo::start("");
     if RUN_MAS {
-        sphere.mas(name);
+        let full = args.iter().any(|a| a == "--full");
+        let on_missing = args
+            .iter()
+            .find_map(|a| a.strip_prefix("--on-missing="))
+            .map_or(Ok(Missing::default()), str::parse);
+        let on_missing = match on_missing {
+            Ok(on_missing) => on_missing,
+            Err(error) => {
+                eprintln!("{}", error);
+                process::exit(1);
+            }
+        };
+        if let Err(error) = sphere.mas(name, full, on_missing) {
+            eprintln!("{}", error);
+            process::exit(1);
+        }
     }
     if WRITE_BOOKTORE {
         gamo::write_booktore(&gamo)
     }
     if WRITE_BUILD {
-        gamo::write_build(&gamo, false);
+        gamo::write_build(&gamo, BUILD_CORPUS, BUILD_LEVEL);
     }
 }